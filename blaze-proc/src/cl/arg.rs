@@ -24,11 +24,13 @@ pub struct Argument {
 impl Argument {
     pub fn ty (&self, generics: &mut Generics, lt: bool) -> syn::Type {
         let name = format_ident!("{}", self.name.to_string().to_uppercase());
-        let (mutability, generify, ty) = self.ty.rustify(self.mutability.is_some(), &name);
+        let (reference, generify, ty) = self.ty.rustify(self.mutability.is_some(), &name);
 
         if let Some(imp) = generify {
             generics.params.push(imp);
-            
+        }
+
+        if let Some(mutability) = reference {
             if lt {
                 return match mutability {
                     true => parse_quote_spanned! { ty.span() => &'__env__ mut #ty },
@@ -1,6 +1,6 @@
 use derive_syn_parse::Parse;
 use proc_macro2::{Ident};
-use syn::{punctuated::Punctuated, Token, LitStr, Visibility, Attribute, parse_quote_spanned, spanned::Spanned};
+use syn::{punctuated::Punctuated, Token, LitStr, Visibility, Attribute, Expr, parse_quote_spanned, spanned::Spanned};
 use super::{Argument};
 
 #[derive(Debug, Parse)]
@@ -20,7 +20,8 @@ pub struct Kernel {
 #[non_exhaustive]
 pub struct KernelAttrs {
     pub attrs: Vec<Attribute>,
-    pub link_name: Option<LitStr>
+    pub link_name: Option<LitStr>,
+    pub extents: Vec<Extent>
 }
 
 impl syn::parse::Parse for KernelAttrs {
@@ -41,7 +42,26 @@ impl syn::parse::Parse for KernelAttrs {
             }
         }
 
-        Ok(Self { attrs, link_name })
+        // `#[extent(unsafe x = n, y = 2 * n)]`
+        // Declared extents can make the kernel safe to call, so they are acknowledged with `unsafe`.
+        let mut extents = Vec::new();
+        let mut i = 0;
+        while i < attrs.len() {
+            if attrs[i].path.is_ident("extent") {
+                let attr = attrs.remove(i);
+                extents.extend(attr.parse_args_with(|input: syn::parse::ParseStream| {
+                    if !input.peek(Token![unsafe]) {
+                        return Err(input.error("extents must be declared as `#[extent(unsafe x = n)]`, promising that the kernel never accesses more elements than declared"))
+                    }
+                    let _ = input.parse::<Token![unsafe]>()?;
+                    Punctuated::<Extent, Token![,]>::parse_terminated(input)
+                })?);
+                continue
+            }
+            i += 1;
+        }
+
+        Ok(Self { attrs, link_name, extents })
     }
 }
 
@@ -50,4 +70,12 @@ struct LinkName {
     #[allow(unused)]
     eq_token: Token![=],
     lit: LitStr
-}
\ No newline at end of file
+}
+/// Maximum number of elements a kernel accesses through a pointer argument.
+#[derive(Debug, Parse)]
+pub struct Extent {
+    pub name: Ident,
+    #[allow(unused)]
+    eq_token: Token![=],
+    pub value: Expr
+}
@@ -1,7 +1,10 @@
 use derive_syn_parse::Parse;
 use proc_macro2::{Ident, TokenStream};
 use quote::{format_ident, quote, ToTokens};
-use syn::{parse_quote, punctuated::Punctuated, Abi, Attribute, Expr, Generics, Token, Visibility};
+use syn::{
+    parse_quote, punctuated::Punctuated, Abi, Attribute, Expr, ExprLit, ExprUnary, Generics, Lit,
+    LitInt, Token, UnOp, Visibility,
+};
 
 use crate::utils::to_pascal_case;
 
@@ -16,14 +19,14 @@ macro_rules! peek_and_parse {
     }};
 }
 
-flat_mod!(ty, kern, arg);
+flat_mod!(ty, kern, arg, signature);
 
 pub fn blaze_c(
     prog_vis: Visibility,
     ident: Ident,
     generics: Generics,
     blaze: Blaze,
-    content: Expr,
+    content: ProgramSource,
 ) -> TokenStream {
     let Blaze { vis, kernels, .. } = blaze;
     let kernel_vis = vis;
@@ -52,6 +55,34 @@ pub fn blaze_c(
     let (prog_imp, prog_ty, prog_wher) = program_generics.split_for_impl();
     let (glob_imp, glob_ty, glob_wher) = generics.split_for_impl();
 
+    // type parameters bound by `ClType` specialize the source
+    let cl_types = generics
+        .type_params()
+        .filter(|x| {
+            x.bounds.iter().any(|bound| match bound {
+                syn::TypeParamBound::Trait(bound) => bound
+                    .path
+                    .segments
+                    .last()
+                    .map_or(false, |x| x.ident == "ClType"),
+                _ => false,
+            })
+        })
+        .map(|x| &x.ident)
+        .collect::<Vec<_>>();
+
+    let program = match content {
+        ProgramSource::Source(ref source) if !cl_types.is_empty() => quote! {
+            ::blaze_rs::core::RawProgram::from_source_specialized_in(
+                &__blaze_ctx__,
+                &[#(::blaze_rs::core::TypeParam::of::<#cl_types>(stringify!(#cl_types))),*],
+                #source,
+                options
+            )
+        },
+        ref other => other.to_token_stream(),
+    };
+
     let kernel_names = kernels.iter().map(|x| &x.ident).collect::<Vec<_>>();
     let kernel_attrs = kernels
         .iter()
@@ -73,9 +104,28 @@ pub fn blaze_c(
         .iter()
         .map(|x| create_kernel(&kernel_vis, &ident, &generics, &program_generics, x));
 
+    let signature_checks = kernels.iter().filter_map(|x| {
+        let source = match content {
+            ProgramSource::Source(ref source) => source,
+            ProgramSource::Il(..) => return None,
+        };
+
+        let attrs = &x.attrs.attrs;
+        let check = check_signature(source, x)?;
+        Some(quote! { #[allow(unused_doc_comments)] #(#attrs)* #check })
+    });
+
+    let kernel_signatures = kernels
+        .iter()
+        .map(|x| {
+            let args = x.args.iter().map(|x| expected_arg(&x.ty));
+            quote! { &[#(#args),*] }
+        })
+        .collect::<Vec<_>>();
+
     let kernel_defs = kernels.iter().map(|x| {
         let name = &x.ident;
-        quote!(#name: ::std::sync::Mutex<::blaze_rs::core::RawKernel>)
+        quote!(#name: ::blaze_rs::core::KernelPool)
     });
 
     quote! {
@@ -88,6 +138,8 @@ pub fn blaze_c(
             #(#(#kernel_attrs)* #kernel_defs),*
         }
 
+        #(#signature_checks)*
+
         impl #glob_imp #ident #glob_ty #glob_wher {
             #[inline(always)]
             #vis fn new (options: Option<&str>) -> ::blaze_rs::core::Result<Self> {
@@ -98,7 +150,7 @@ pub fn blaze_c(
         impl #prog_imp #ident #prog_ty #prog_wher {
             #vis fn new_in (ctx: C, options: Option<&str>) -> ::blaze_rs::core::Result<Self> {
                 let __blaze_ctx__ = ctx;
-                let (__blaze_inner__, __blaze_kernels__) = ::blaze_rs::core::RawProgram::from_source_in(&__blaze_ctx__, #content, options)?;
+                let (__blaze_inner__, __blaze_kernels__) = #program?;
 
                 #(
                     #[allow(unused_doc_comments)]
@@ -118,7 +170,10 @@ pub fn blaze_c(
                     #[allow(unused_doc_comments)]
                     #(#kernel_attrs)*
                     let #kernel_names = match #kernel_names {
-                        Some(__x) => ::std::sync::Mutex::new(__x),
+                        Some(__x) => {
+                            ::blaze_rs::signature::validate_strict(&__x, #kernel_signatures)?;
+                            ::blaze_rs::core::KernelPool::new(__x)?
+                        },
                         None => return Err(::blaze_rs::core::Error::new(::blaze_rs::core::ErrorKind::InvalidKernel, concat!("kernel '", stringify!(#kernel_names), "' not found")))
                     };
                 )*
@@ -167,6 +222,41 @@ fn create_kernel(
     } = kernel;
     let mut generics = parse_quote! { <'__scope__, '__env__: '__scope__> };
     let (parent_imp, parent_ty, parent_wher) = parent_generics.split_for_impl();
+
+    let mut extents = Vec::with_capacity(attrs.extents.len());
+    for Extent { name, value, .. } in attrs.extents.iter() {
+        let ty = match args.iter().find(|x| &x.name == name) {
+            Some(Argument {
+                ty: Type::Pointer(_, ty),
+                ..
+            }) => ty.rustify_ptr(),
+            Some(_) => {
+                return syn::Error::new_spanned(
+                    name,
+                    "extents can only be declared for pointer arguments",
+                )
+                .to_compile_error()
+            }
+            None => {
+                return syn::Error::new_spanned(name, format!("unknown argument `{name}`"))
+                    .to_compile_error()
+            }
+        };
+
+        let name_str = name.to_string();
+        extents.push(quote! {
+            ::blaze_rs::buffer::check_extent(#name_str, ::blaze_rs::buffer::KernelPointer::<#ty>::extent(#name)?, #value)?
+        });
+    }
+
+    // kernels are safe to call when the extents of all their pointers are declared, and they don't write into images or pipes
+    let safe = !extents.is_empty()
+        && args.iter().all(|x| match x.ty {
+            Type::Pointer(..) => attrs.extents.iter().any(|e| e.name == x.name),
+            Type::Image(_, access) | Type::Pipe(access, _) => !access.is_mut(),
+            _ => true,
+        });
+
     let attrs = match attrs.attrs.is_empty() {
         true => None,
         false => {
@@ -188,13 +278,18 @@ fn create_kernel(
     assert_eq!(name.len(), new.len());
     //panic!("{name:?}: {new:?}");
 
-    let pointer_names = args
+    let complete = args
         .iter()
-        .filter_map(|x| {
-            if x.ty.is_pointer() {
-                Some(&x.name)
-            } else {
-                None
+        .filter(|x| x.ty.is_pointer())
+        .map(|x| {
+            let name = &x.name;
+            match x.ty {
+                Type::Pointer(true, _) => quote! {
+                    ::blaze_rs::buffer::KernelPointer::complete_mut(#name, &__blaze_inner__)?
+                },
+                _ => quote! {
+                    ::blaze_rs::buffer::KernelPointer::complete(#name, &__blaze_inner__)?
+                },
             }
         })
         .collect::<Vec<_>>();
@@ -203,6 +298,12 @@ fn create_kernel(
         .enumerate()
         .map(|(i, x)| set_arg(x, u32::try_from(i).unwrap()))
         .collect::<Vec<_>>();
+
+    // the local memory size of the devices is queried once per kernel pool
+    let local_mem = match args.iter().any(|x| matches!(x.ty, Type::Local(_))) {
+        true => quote! { let __blaze_local_mem__ = self.#ident.local_mem_size()?; },
+        false => TokenStream::new(),
+    };
     //generics.params.extend(impl_generics.params.iter().cloned());
 
     let blocking_ident = format_ident!("{ident}_blocking");
@@ -255,64 +356,116 @@ fn create_kernel(
     generics.params.push(parse_quote! { const N: usize });
     let (r#impl, _, r#where) = generics.split_for_impl();
 
-    quote! {
-        #[::blaze_rs::blaze_proc::newtype]
-        #attrs #vis type #consumer_name #event_type = ::core::marker::PhantomData<(#(#event_new),*)>;
-        #attrs #vis type #event_name #event_type = ::blaze_rs::event::Event<#consumer_name #event_type>;
+    let unsafety = match safe {
+        true => None,
+        false => Some(quote! { unsafe }),
+    };
 
-        impl #parent_imp #parent #parent_ty #parent_wher {
-            #attrs
-            #vis unsafe fn #ident #r#impl (&self, scope: &'__scope__ ::blaze_rs::context::Scope<'__scope__, '__env__, C>, #(#name: #new,)* global_work_dims: [usize; N], local_work_dims: impl Into<Option<[usize; N]>>, wait: ::blaze_rs::WaitList) -> ::blaze_rs::prelude::Result<#event_name #event_type> #r#where {
-                let mut wait = match wait {
-                    ::blaze_rs::WaitList::Some(x) => x.to_vec(),
-                    ::blaze_rs::WaitList::None => ::std::vec::Vec::new()
-                };
+    let scope_body = quote! {
+        let mut wait = match wait {
+            ::blaze_rs::WaitList::Some(x) => x.to_vec(),
+            ::blaze_rs::WaitList::None => ::std::vec::Vec::new()
+        };
 
-                let mut __blaze_kernel__ = match self.#ident.lock() {
-                    Ok(x) => x,
-                    Err(e) => e.into_inner()
-                };
+        #(#extents;)*
+        #local_mem
 
-                #(#set);*;
+        let mut __blaze_kernel__ = self.#ident.get()?;
 
-                let __blaze_inner__ = __blaze_kernel__.enqueue_phantom_with_scope(&scope, global_work_dims, local_work_dims, Some(&wait))?;
-                drop(__blaze_kernel__);
-                let __blaze_inner__ = ::blaze_rs::event::Event::map_consumer(__blaze_inner__, #consumer_name);
+        let __blaze_inner__ = unsafe {
+            #(#set);*;
+            __blaze_kernel__.enqueue_phantom_with_scope(&scope, global_work_dims, local_work_dims, Some(&wait))?
+        };
+        drop(__blaze_kernel__);
+        let __blaze_inner__ = ::blaze_rs::event::Event::map_consumer(__blaze_inner__, #consumer_name);
 
-                #(
-                    ::blaze_rs::buffer::KernelPointer::complete(#pointer_names, &__blaze_inner__)?;
-                )*
+        #(#complete;)*
 
-                return Ok(__blaze_inner__)
-            }
+        return Ok(__blaze_inner__)
+    };
 
-            #attrs
-            #vis unsafe fn #blocking_ident #blocking_impl (&self, #(#name: #blocking_new,)* global_work_dims: [usize; N], local_work_dims: impl Into<Option<[usize; N]>>, wait: ::blaze_rs::WaitList) -> ::blaze_rs::prelude::Result<()> #blocking_where {
+    let blocking_body = quote! {
+        let mut wait = match wait {
+            ::blaze_rs::WaitList::Some(x) => x.to_vec(),
+            ::blaze_rs::WaitList::None => ::std::vec::Vec::new()
+        };
+
+        #(#extents;)*
+        #local_mem
+
+        let mut __blaze_kernel__ = self.#ident.get()?;
+
+        let __blaze_inner__ = unsafe {
+            #(#set);*;
+            __blaze_kernel__.enqueue_unchecked(::blaze_rs::context::Context::next_queue(&self.__blaze_ctx__), global_work_dims, local_work_dims, Some(&wait))?
+        };
+
+        drop(__blaze_kernel__);
+
+        #(#complete;)*
+
+        return __blaze_inner__.join_by_ref();
+    };
+
+    #[cfg(feature = "futures")]
+    let async_kernel = {
+        let async_ident = format_ident!("{ident}_async");
+        let async_body = quote! {
+            let __blaze_inner__ = {
                 let mut wait = match wait {
                     ::blaze_rs::WaitList::Some(x) => x.to_vec(),
                     ::blaze_rs::WaitList::None => ::std::vec::Vec::new()
                 };
 
-                let mut __blaze_kernel__ = match self.#ident.lock() {
-                    Ok(x) => x,
-                    Err(e) => e.into_inner()
-                };
+                #(#extents;)*
+                #local_mem
+        #local_mem
 
-                #(#set);*;
+                let mut __blaze_kernel__ = self.#ident.get()?;
 
                 let __blaze_inner__ = unsafe {
+                    #(#set);*;
                     __blaze_kernel__.enqueue_unchecked(::blaze_rs::context::Context::next_queue(&self.__blaze_ctx__), global_work_dims, local_work_dims, Some(&wait))?
                 };
 
                 drop(__blaze_kernel__);
 
-                #(
-                    ::blaze_rs::buffer::KernelPointer::complete(#pointer_names, &__blaze_inner__)?;
-                )*
+                #(#complete;)*
+                __blaze_inner__
+            };
+
+            // if the future is dropped before the kernel completes, block until it does, so the borrowed arguments outlive it
+            return ::blaze_rs::event::Event::new_noop(__blaze_inner__).join_async_guarded()?.await;
+        };
 
-                return __blaze_inner__.join_by_ref();
+        quote! {
+            #attrs
+            #vis async #unsafety fn #async_ident #blocking_impl (&self, #(#name: #blocking_new,)* global_work_dims: impl Into<::blaze_rs::core::NdRange<N>>, local_work_dims: impl Into<::blaze_rs::core::tuning::LocalSize<N>>, wait: ::blaze_rs::WaitList<'_>) -> ::blaze_rs::prelude::Result<()> #blocking_where {
+                #async_body
             }
         }
+    };
+    #[cfg(not(feature = "futures"))]
+    let async_kernel = TokenStream::new();
+
+    quote! {
+        #[::blaze_rs::blaze_proc::newtype]
+        #attrs #vis type #consumer_name #event_type = ::core::marker::PhantomData<(#(#event_new),*)>;
+        #attrs #vis type #event_name #event_type = ::blaze_rs::event::Event<#consumer_name #event_type>;
+
+        impl #parent_imp #parent #parent_ty #parent_wher {
+            #attrs
+            #vis #unsafety fn #ident #r#impl (&self, scope: &'__scope__ ::blaze_rs::context::Scope<'__scope__, '__env__, C>, #(#name: #new,)* global_work_dims: impl Into<::blaze_rs::core::NdRange<N>>, local_work_dims: impl Into<::blaze_rs::core::tuning::LocalSize<N>>, wait: ::blaze_rs::WaitList) -> ::blaze_rs::prelude::Result<#event_name #event_type> #r#where {
+                #scope_body
+            }
+
+            #attrs
+            #vis #unsafety fn #blocking_ident #blocking_impl (&self, #(#name: #blocking_new,)* global_work_dims: impl Into<::blaze_rs::core::NdRange<N>>, local_work_dims: impl Into<::blaze_rs::core::tuning::LocalSize<N>>, wait: ::blaze_rs::WaitList) -> ::blaze_rs::prelude::Result<()> #blocking_where {
+                #blocking_body
+            }
+
+            #async_kernel
+        }
     }
 }
 
@@ -320,13 +473,34 @@ fn set_arg(arg: &Argument, idx: u32) -> TokenStream {
     let Argument { name, .. } = arg;
 
     match arg.ty {
-        Type::Pointer(_, _) => quote! {
+        Type::Pointer(true, _) => quote! {
+            ::blaze_rs::buffer::KernelPointer::set_arg_mut(#name, &mut __blaze_kernel__, &mut wait, #idx)?
+        },
+
+        Type::Pointer(false, _) => quote! {
             ::blaze_rs::buffer::KernelPointer::set_arg(#name, &mut __blaze_kernel__, &mut wait, #idx)?
         },
 
-        Type::Image2d => {
-            quote! { __blaze_kernel__.set_argument(#idx, ::blaze_rs::image::DynImage2D::id_ref(#name))? }
+        Type::Local(_) => quote! {
+            ::blaze_rs::core::LocalMem::set_arg_with_max(&#name, &mut __blaze_kernel__, #idx, __blaze_local_mem__)?
+        },
+
+        Type::Image(ty, _) => {
+            let ty = format_ident!("{}", ty.mem_object_type());
+            quote! {
+                ::blaze_rs::memobj::KernelImage::set_arg(#name, &mut __blaze_kernel__, #idx, ::blaze_rs::memobj::MemObjectType::#ty)?
+            }
+        }
+
+        Type::Sampler => {
+            quote! { __blaze_kernel__.set_argument(#idx, #name.id())? }
         }
+
+        Type::Pipe(_, ref ty) => {
+            let ty = ty.rustify_ptr();
+            quote! { ::blaze_rs::core::KernelPipe::<#ty>::set_arg(#name, &mut __blaze_kernel__, #idx)? }
+        }
+
         _ => quote! { __blaze_kernel__.set_argument(#idx, #name)? },
     }
 }
@@ -349,3 +523,92 @@ pub struct Link {
     pub eq_token: Token![=],
     pub meta: Expr,
 }
+
+#[derive(Parse)]
+pub struct SpecConstants {
+    #[allow(unused)]
+    #[paren]
+    pub paren_token: syn::token::Paren,
+    #[inside(paren_token)]
+    #[call(Punctuated::parse_terminated)]
+    pub constants: Punctuated<SpecConstant, Token![,]>,
+}
+
+#[derive(Parse)]
+pub struct SpecConstant {
+    pub id: LitInt,
+    #[allow(unused)]
+    pub eq_token: Token![=],
+    pub value: Expr,
+}
+
+impl SpecConstant {
+    /// Rejects unsuffixed numeric literals, whose type (and thus size) would otherwise default to `i32`/`f64`
+    /// regardless of the width of the SPIR-V constant.
+    pub fn check_typed(&self) -> syn::Result<()> {
+        let mut value = &self.value;
+        loop {
+            match value {
+                Expr::Paren(x) => value = &x.expr,
+                Expr::Group(x) => value = &x.expr,
+                Expr::Unary(ExprUnary {
+                    op: UnOp::Neg(_),
+                    expr,
+                    ..
+                }) => value = expr,
+                _ => break,
+            }
+        }
+
+        let suffix = match value {
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(x), ..
+            }) => x.suffix(),
+            Expr::Lit(ExprLit {
+                lit: Lit::Float(x), ..
+            }) => x.suffix(),
+            _ => return Ok(()),
+        };
+
+        match suffix {
+            "" => Err(syn::Error::new_spanned(
+                &self.value,
+                "specialization constant literals must have a type suffix, like `42u32`",
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub enum ProgramSource {
+    Source(Expr),
+    Il(Expr, Option<Punctuated<SpecConstant, Token![,]>>),
+}
+
+impl ToTokens for ProgramSource {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(match self {
+            ProgramSource::Source(source) => quote! {
+                ::blaze_rs::core::RawProgram::from_source_cached_in(&__blaze_ctx__, #source, options)
+            },
+
+            ProgramSource::Il(il, None) => quote! {
+                ::blaze_rs::core::RawProgram::from_il_in(&__blaze_ctx__, #il, options)
+            },
+
+            ProgramSource::Il(il, Some(constants)) => {
+                let ids = constants.iter().map(|x| &x.id);
+                let values = constants.iter().map(|x| &x.value);
+
+                quote! {
+                    ::blaze_rs::core::RawProgram::from_il_with_constants_in(
+                        &__blaze_ctx__,
+                        #il,
+                        &[#(::blaze_rs::core::SpecConstant::new(#ids, &#values)),*],
+                        options
+                    )
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,196 @@
+use super::{Kernel, Type};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Expr, GenericArgument, Lit, LitStr, PathArguments};
+
+/// Generates a constant that checks the kernel's signature against the OpenCL C source at compile time.
+/// Only string literals, `include_str!`, `concat!` and paths (which must name a `const`) are checked,
+/// since other sources (like function calls or `format!`) may not be evaluable in a const context.
+pub fn check_signature(source: &Expr, kernel: &Kernel) -> Option<TokenStream> {
+    match source {
+        Expr::Lit(lit) if matches!(lit.lit, Lit::Str(_)) => {}
+        Expr::Path(_) => {}
+        Expr::Macro(mac) => match mac.mac.path.segments.last() {
+            Some(x) if x.ident == "include_str" || x.ident == "concat" => {}
+            _ => return None,
+        },
+        _ => return None,
+    }
+
+    let name = match kernel.attrs.link_name {
+        Some(ref name) => name.value(),
+        None => kernel.ident.to_string(),
+    };
+
+    let args = kernel
+        .args
+        .iter()
+        .map(|x| expected_arg(&x.ty))
+        .collect::<Vec<_>>();
+
+    let mut arms = vec![
+        mismatch_arm(
+            quote! { MissingKernel },
+            format!("kernel `{name}` isn't declared in the OpenCL source"),
+        ),
+        mismatch_arm(
+            quote! { ArgCount },
+            format!(
+                "kernel `{name}` takes {} arguments in Rust, but a different number in the OpenCL source",
+                args.len()
+            ),
+        ),
+    ];
+
+    for (i, arg) in kernel.args.iter().enumerate() {
+        let prefix = format!("argument `{}` of kernel `{name}`", arg.name);
+        let pointer = match arg.ty {
+            Type::Pointer(..) | Type::Local(_) => {
+                format!("{prefix} is a pointer in Rust, but not in the OpenCL source")
+            }
+            _ => format!("{prefix} is a pointer in the OpenCL source, but not in Rust"),
+        };
+
+        let address_space = match arg.ty {
+            Type::Local(_) => format!("{prefix} must be `__local` in the OpenCL source"),
+            _ => format!("{prefix} must be `__global` or `__constant` in the OpenCL source"),
+        };
+
+        let constness = match arg.ty {
+            Type::Pointer(true, _) => {
+                format!("{prefix} is `*mut` in Rust, but `const` in the OpenCL source")
+            }
+            _ => format!("{prefix} is `*const` in Rust, but not `const` in the OpenCL source"),
+        };
+
+        let ty = match c_type(&arg.ty) {
+            Some(ty) => {
+                format!("{prefix} has a different type in the OpenCL source (expected `{ty}`)")
+            }
+            None => format!("{prefix} has a different type in the OpenCL source"),
+        };
+
+        for (kind, msg) in [
+            ("Type", ty),
+            ("Pointer", pointer),
+            ("AddressSpace", address_space),
+            ("Const", constness),
+        ] {
+            let kind = syn::Ident::new(kind, proc_macro2::Span::call_site());
+            arms.push(mismatch_arm(
+                quote! { Arg(#i, ::blaze_rs::signature::ArgMismatch::#kind) },
+                msg,
+            ));
+        }
+    }
+
+    let name = LitStr::new(&name, proc_macro2::Span::call_site());
+    return Some(quote! {
+        const _: () = match ::blaze_rs::signature::check(#source, #name, &[#(#args),*]) {
+            #(#arms,)*
+            _ => {}
+        };
+    });
+}
+
+fn mismatch_arm(pattern: TokenStream, msg: String) -> TokenStream {
+    let msg = LitStr::new(&msg, proc_macro2::Span::call_site());
+    quote! { ::blaze_rs::signature::Mismatch::#pattern => panic!(#msg) }
+}
+
+pub fn expected_arg(ty: &Type) -> TokenStream {
+    match ty {
+        Type::Pointer(mutable, ty) => {
+            let ty = element(ty);
+            quote! { ::blaze_rs::signature::Arg::Pointer { mutable: #mutable, ty: #ty } }
+        }
+        Type::Local(ty) => {
+            let ty = element(ty);
+            quote! { ::blaze_rs::signature::Arg::Local(#ty) }
+        }
+        Type::Path(_) | Type::Array(..) => {
+            let ty = element(ty);
+            quote! { ::blaze_rs::signature::Arg::Value(#ty) }
+        }
+        Type::Image(..) | Type::Sampler | Type::Pipe(..) => {
+            quote! { ::blaze_rs::signature::Arg::Other }
+        }
+    }
+}
+
+fn element(ty: &Type) -> TokenStream {
+    let (name, width) = element_name(ty).unwrap_or(("Unknown", 1));
+    let name = syn::Ident::new(name, proc_macro2::Span::call_site());
+    quote! { ::blaze_rs::signature::Element::new(::blaze_rs::signature::Scalar::#name, #width) }
+}
+
+/// Name of the OpenCL C type of a scalar or vector argument, or of the pointee of a pointer argument.
+fn c_type(ty: &Type) -> Option<String> {
+    let (name, width) = element_name(ty)?;
+    let c = match name {
+        "Char" => "char",
+        "UChar" => "uchar",
+        "Short" => "short",
+        "UShort" => "ushort",
+        "Int" => "int",
+        "UInt" => "uint",
+        "Long" => "long",
+        "ULong" => "ulong",
+        "Half" => "half",
+        "Float" => "float",
+        "Double" => "double",
+        _ => return None,
+    };
+
+    match width {
+        1 => Some(c.to_string()),
+        width => Some(format!("{c}{width}")),
+    }
+}
+
+/// Name of the scalar and number of components of the type.
+fn element_name(ty: &Type) -> Option<(&'static str, u8)> {
+    let path = match ty {
+        Type::Pointer(_, ty) | Type::Local(ty) => return element_name(ty),
+        Type::Path(path) => &path.path,
+        Type::Array(..) | Type::Image(..) | Type::Sampler | Type::Pipe(..) => return None,
+    };
+
+    let segment = path.segments.last()?;
+    let inner = || match segment.arguments {
+        PathArguments::AngleBracketed(ref args) => match args.args.first() {
+            Some(GenericArgument::Type(syn::Type::Path(inner))) => {
+                element_name(&Type::Path(inner.clone()))
+            }
+            _ => None,
+        },
+        _ => None,
+    };
+
+    let name = match segment.ident.to_string().as_str() {
+        "i8" | "cl_char" => "Char",
+        "u8" | "cl_uchar" => "UChar",
+        "i16" | "cl_short" => "Short",
+        "u16" | "cl_ushort" => "UShort",
+        "i32" | "cl_int" => "Int",
+        "u32" | "cl_uint" => "UInt",
+        "i64" | "cl_long" => "Long",
+        "u64" | "cl_ulong" => "ULong",
+        "f16" | "cl_half" => "Half",
+        "f32" | "cl_float" => "Float",
+        "f64" | "cl_double" => "Double",
+        // `MaybeUninit<T>` has the same layout as `T`
+        "MaybeUninit" => return inner(),
+        // vectors of `blaze_rs::core::vector`
+        ident @ ("Vec2" | "Vec3" | "Vec4" | "Vec8" | "Vec16") => {
+            let width = ident[3..].parse().ok()?;
+            return match inner()? {
+                (name, 1) => Some((name, width)),
+                _ => None,
+            };
+        }
+        _ => return None,
+    };
+
+    Some((name, 1))
+}
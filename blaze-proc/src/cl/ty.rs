@@ -1,14 +1,70 @@
 use proc_macro2::{Ident};
+use quote::quote_spanned;
 use syn::{parse::Parse, LitInt, TypePath, token::{Mut, Star}, bracketed, parse_quote_spanned, spanned::Spanned, Token, GenericParam, custom_keyword, parse_quote};
 
+custom_keyword!(image1d);
+custom_keyword!(image1d_array);
+custom_keyword!(image1d_buffer);
 custom_keyword!(image2d);
+custom_keyword!(image2d_array);
+custom_keyword!(image3d);
+custom_keyword!(sampler_t);
+custom_keyword!(pipe);
+custom_keyword!(local);
+custom_keyword!(read);
+custom_keyword!(write);
+custom_keyword!(read_write);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageType {
+    Image1d,
+    Image1dArray,
+    Image1dBuffer,
+    Image2d,
+    Image2dArray,
+    Image3d
+}
+
+impl ImageType {
+    /// Variant of `MemObjectType` of the image.
+    #[inline(always)]
+    pub const fn mem_object_type (self) -> &'static str {
+        match self {
+            Self::Image1d => "Image1D",
+            Self::Image1dArray => "Image1DArray",
+            Self::Image1dBuffer => "Image1DBuffer",
+            Self::Image2d => "Image2D",
+            Self::Image2dArray => "Image2DArray",
+            Self::Image3d => "Image3D"
+        }
+    }
+}
+
+/// Access qualifier of an image or pipe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    ReadWrite
+}
+
+impl Access {
+    /// Images and pipes the kernel may write to are taken by mutable reference.
+    #[inline(always)]
+    pub const fn is_mut (self) -> bool {
+        !matches!(self, Self::Read)
+    }
+}
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Type {
     Array (Box<Type>, LitInt),
     Path (TypePath),
     Pointer (bool, Box<Type>),
-    Image2d
+    Local (Box<Type>),
+    Image (ImageType, Access),
+    Sampler,
+    Pipe (Access, Box<Type>)
 }
 
 impl Type {
@@ -24,30 +80,44 @@ impl Type {
     #[inline(always)]
     pub fn is_define (&self) -> ::std::primitive::bool {
         match self {
-            Self::Pointer { .. } | Self::Image2d => true,
+            Self::Pointer { .. } | Self::Image(..) | Self::Sampler | Self::Pipe(..) => true,
             _ => false
         }
     }
 
-    pub fn rustify (&self, mutability: bool, name: &Ident) -> (bool, Option<GenericParam>, syn::Type) {
+    /// Returns whether the argument is taken by reference (and if it's mutable), its generic parameter (if any) and its type.
+    pub fn rustify (&self, mutability: bool, name: &Ident) -> (Option<bool>, Option<GenericParam>, syn::Type) {
         match self {
             Type::Array(ty, len) => {
                 let (_, gen, ty) = ty.rustify(mutability, name);
                 let v = parse_quote_spanned! { ty.span() => [#ty; #len] };
-                (false, gen, v)
+                (None, gen, v)
             },
 
-            Type::Path(ty) => (false, None, syn::Type::Path(ty.clone())),
+            Type::Path(ty) => (None, None, syn::Type::Path(ty.clone())),
 
             Type::Pointer(mutability, ty) => {
                 let ty = ty.rustify_ptr();
                 let param = parse_quote! { #name: ::blaze_rs::buffer::KernelPointer<#ty> };
-                (*mutability, Some(param), parse_quote_spanned! { name.span() => #name })
+                (Some(*mutability), Some(param), parse_quote_spanned! { name.span() => #name })
+            },
+
+            Type::Local(ty) => {
+                let ty = ty.rustify_ptr();
+                (None, None, parse_quote_spanned! { ty.span() => ::blaze_rs::core::LocalMem<#ty> })
             },
 
-            Type::Image2d => {
-                let param = parse_quote! { #name: ::blaze_rs::image::DynImage2D };
-                (false, Some(param), parse_quote_spanned! { name.span() => #name })
+            Type::Image(_, access) => {
+                let param = parse_quote! { #name: ::blaze_rs::memobj::KernelImage };
+                (Some(access.is_mut()), Some(param), parse_quote_spanned! { name.span() => #name })
+            },
+
+            Type::Sampler => (Some(false), None, parse_quote_spanned! { name.span() => ::blaze_rs::core::Sampler }),
+
+            Type::Pipe(access, ty) => {
+                let ty = ty.rustify_ptr();
+                let param = parse_quote! { #name: ::blaze_rs::core::KernelPipe<#ty> };
+                (Some(access.is_mut()), Some(param), parse_quote_spanned! { name.span() => #name })
             },
         }
     }
@@ -56,13 +126,41 @@ impl Type {
         match self {
             Self::Array(ty, _) => ty.rustify_ptr(),
             Self::Path(x) => syn::Type::Path(x.clone()),
-            Self::Image2d => todo!(),
-            #[allow(unused)]
-            Self::Pointer(_, ty) => todo!(),
+            Self::Image(..) | Self::Sampler | Self::Pipe(..) => syn::Type::Verbatim(quote_spanned! { proc_macro2::Span::call_site() =>
+                ::core::compile_error!("images, samplers and pipes cannot be pointed to by kernel arguments")
+            }),
+            Self::Pointer(..) | Self::Local(..) => syn::Type::Verbatim(quote_spanned! { proc_macro2::Span::call_site() =>
+                ::core::compile_error!("kernel arguments cannot be pointers to pointers")
+            }),
         }
     }
 }
 
+/// Parses the optional access qualifier of an image, like `image2d<write>`. Images are read-only by default.
+fn parse_image (input: syn::parse::ParseStream, ty: ImageType) -> syn::Result<Type> {
+    if !input.peek(Token![<]) {
+        return Ok(Type::Image(ty, Access::Read))
+    }
+
+    let _ = input.parse::<Token![<]>()?;
+    let access = parse_access(input)?.ok_or_else(|| input.error("expected `read`, `write` or `read_write`"))?;
+    let _ = input.parse::<Token![>]>()?;
+    return Ok(Type::Image(ty, access))
+}
+
+fn parse_access (input: syn::parse::ParseStream) -> syn::Result<Option<Access>> {
+    if peek_and_parse!(read_write in input) {
+        return Ok(Some(Access::ReadWrite))
+    }
+    if peek_and_parse!(write in input) {
+        return Ok(Some(Access::Write))
+    }
+    if peek_and_parse!(read in input) {
+        return Ok(Some(Access::Read))
+    }
+    Ok(None)
+}
+
 impl Parse for Type {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         if peek_and_parse!(Star in input) {
@@ -81,10 +179,56 @@ impl Parse for Type {
             return Ok(Self::Array(ty, len))
         }
 
+        // `local<[T]>`
+        if peek_and_parse!(local in input) {
+            let _ = input.parse::<Token![<]>()?;
+            let content; bracketed!(content in input);
+            let ty = Box::new(content.parse()?);
+            let _ = input.parse::<Token![>]>()?;
+            return Ok(Self::Local(ty))
+        }
+
+        // `image2d`, `image3d<write>`, ...
+        if peek_and_parse!(image1d_array in input) {
+            return parse_image(input, ImageType::Image1dArray)
+        }
+        if peek_and_parse!(image1d_buffer in input) {
+            return parse_image(input, ImageType::Image1dBuffer)
+        }
+        if peek_and_parse!(image1d in input) {
+            return parse_image(input, ImageType::Image1d)
+        }
+        if peek_and_parse!(image2d_array in input) {
+            return parse_image(input, ImageType::Image2dArray)
+        }
         if peek_and_parse!(image2d in input) {
-            return Ok(Self::Image2d)
+            return parse_image(input, ImageType::Image2d)
+        }
+        if peek_and_parse!(image3d in input) {
+            return parse_image(input, ImageType::Image3d)
+        }
+
+        if peek_and_parse!(sampler_t in input) {
+            return Ok(Self::Sampler)
+        }
+
+        // `pipe<T>` or `pipe<write, T>`. Pipes are read-only by default.
+        if peek_and_parse!(pipe in input) {
+            let _ = input.parse::<Token![<]>()?;
+            let access = match parse_access(input)? {
+                Some(Access::ReadWrite) => return Err(input.error("pipes cannot be `read_write`")),
+                Some(access) => {
+                    let _ = input.parse::<Token![,]>()?;
+                    access
+                },
+                None => Access::Read
+            };
+
+            let ty = Box::new(input.parse()?);
+            let _ = input.parse::<Token![>]>()?;
+            return Ok(Self::Pipe(access, ty))
         }
 
         input.parse().map(Self::Path)
     }
-}
\ No newline at end of file
+}
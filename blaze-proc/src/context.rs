@@ -1,31 +1,71 @@
-use proc_macro2::{TokenStream};
-use quote::quote;
-use syn::{ItemStatic};
+use derive_syn_parse::Parse;
+use proc_macro2::{TokenStream, Ident};
+use quote::{quote, format_ident};
+use syn::{ItemStatic, Token, custom_keyword};
+
+custom_keyword!(name);
+
+/// Arguments of `#[global_context]`, like `#[global_context(name = Compute)]`
+#[derive(Parse)]
+pub struct GlobalArgs {
+    #[peek(name)]
+    pub name: Option<GlobalName>
+}
+
+#[derive(Parse)]
+pub struct GlobalName {
+    #[allow(unused)]
+    name_token: name,
+    #[allow(unused)]
+    eq_token: Token![=],
+    pub marker: Ident
+}
 
 #[inline(always)]
-pub fn global_context (input: ItemStatic) -> TokenStream {
+pub fn global_context (args: GlobalArgs, input: ItemStatic) -> TokenStream {
     let ItemStatic { attrs, vis, static_token, mutability, ident, colon_token, ty, eq_token, expr, semi_token } = input;
 
-    quote! {
-        #(#attrs)*
-        #vis #static_token #mutability #ident #colon_token ::blaze_rs::once_cell::sync::Lazy<#ty> #eq_token ::blaze_rs::once_cell::sync::Lazy::new(|| #expr.unwrap()) #semi_token
+    let register = match args.name {
+        Some(GlobalName { marker, .. }) => {
+            let doc = format!("Marker of the global context [`{ident}`]");
+            quote! {
+                #[doc = #doc]
+                #vis enum #marker {}
 
-        #[doc(hidden)]
-        #[no_mangle]
-        extern "Rust" fn __blaze__global__as_raw () -> &'static ::blaze_rs::context::RawContext {
-            ::blaze_rs::context::Context::as_raw(::blaze_rs::once_cell::sync::Lazy::force(&#ident))
-        }
+                unsafe impl ::blaze_rs::context::GlobalMarker for #marker {
+                    type Context = #ty;
 
-        #[doc(hidden)]
-        #[no_mangle]
-        extern "Rust" fn __blaze__global__queues () -> &'static [::blaze_rs::context::CommandQueue] {
-            ::blaze_rs::context::Context::queues(::blaze_rs::once_cell::sync::Lazy::force(&#ident))
-        }
+                    #[inline(always)]
+                    fn context () -> &'static #ty {
+                        ::blaze_rs::once_cell::sync::Lazy::force(&#ident)
+                    }
+                }
+            }
+        },
+
+        None => {
+            let register = format_ident!("__BLAZE_GLOBAL_{ident}");
+            quote! {
+                #[doc(hidden)]
+                #[::blaze_rs::linkme::distributed_slice(::blaze_rs::context::GLOBAL_CONTEXTS)]
+                #[linkme(crate = ::blaze_rs::linkme)]
+                static #register : fn() -> &'static (dyn ::blaze_rs::context::Context + Sync) = || {
+                    let ctx: &'static #ty = ::blaze_rs::once_cell::sync::Lazy::force(&#ident);
+                    ctx
+                };
 
-        #[doc(hidden)]
-        #[no_mangle]
-        extern "Rust" fn __blaze__global__next_queue () -> &'static ::blaze_rs::context::CommandQueue {
-            ::blaze_rs::context::Context::next_queue(::blaze_rs::once_cell::sync::Lazy::force(&#ident))
+                // declaring a second default global context fails to compile (or link, if it's in another crate)
+                #[doc(hidden)]
+                #[no_mangle]
+                static __BLAZE_DEFAULT_GLOBAL_CONTEXT : () = ();
+            }
         }
+    };
+
+    quote! {
+        #(#attrs)*
+        #vis #static_token #mutability #ident #colon_token ::blaze_rs::once_cell::sync::Lazy<#ty> #eq_token ::blaze_rs::once_cell::sync::Lazy::new(|| #expr.unwrap()) #semi_token
+
+        #register
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,128 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Expr, Fields, Meta, NestedMeta, Type};
+
+pub fn derive_kernel_arg(items: DeriveInput) -> TokenStream {
+    match kernel_arg(items) {
+        Ok(x) => x,
+        Err(e) => e.to_compile_error(),
+    }
+}
+
+fn kernel_arg(items: DeriveInput) -> syn::Result<TokenStream> {
+    let DeriveInput {
+        attrs,
+        ident,
+        generics,
+        data,
+        ..
+    } = items;
+
+    if !generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            generics,
+            "`KernelArg` cannot be derived for generic types",
+        ));
+    }
+
+    let is_repr_c = attrs
+        .iter()
+        .filter(|x| x.path.is_ident("repr"))
+        .filter_map(|x| x.parse_meta().ok())
+        .any(|x| match x {
+            Meta::List(list) => list
+                .nested
+                .iter()
+                .any(|x| matches!(x, NestedMeta::Meta(Meta::Path(path)) if path.is_ident("C"))),
+            _ => false,
+        });
+
+    if !is_repr_c {
+        return Err(Error::new_spanned(
+            &ident,
+            "`KernelArg` can only be derived for `#[repr(C)]` structs",
+        ));
+    }
+
+    let fields = match data {
+        Data::Struct(x) => match x.fields {
+            Fields::Named(x) => x.named.into_iter().collect::<Vec<_>>(),
+            Fields::Unnamed(x) => x.unnamed.into_iter().collect::<Vec<_>>(),
+            Fields::Unit => Vec::new(),
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &ident,
+                "`KernelArg` can only be derived for structs",
+            ))
+        }
+    };
+
+    if fields.is_empty() {
+        return Err(Error::new_spanned(
+            &ident,
+            "`KernelArg` cannot be derived for structs without fields",
+        ));
+    }
+
+    let mut parts = vec![quote! { ::blaze_rs::core::ClPart::Str("typedef struct {\n") }];
+
+    for (i, field) in fields.iter().enumerate() {
+        let name = match field.ident {
+            Some(ref name) => name.to_string(),
+            None => format!("_{i}"),
+        };
+
+        // `[[T; N]; M]` is declared as `T name[M][N]`
+        let mut ty = &field.ty;
+        let mut lens = Vec::<&Expr>::new();
+        while let Type::Array(array) = ty {
+            lens.push(&array.len);
+            ty = &array.elem;
+        }
+
+        let decl = format!(" {name}");
+        parts.push(quote! { ::blaze_rs::core::ClPart::Str("    ") });
+        parts.push(
+            quote! { ::blaze_rs::core::ClPart::Str(<#ty as ::blaze_rs::core::KernelArg>::CL_TYPE) },
+        );
+        parts.push(quote! { ::blaze_rs::core::ClPart::Str(#decl) });
+        for len in lens {
+            parts.push(quote! {
+                ::blaze_rs::core::ClPart::Str("["),
+                ::blaze_rs::core::ClPart::Int(#len),
+                ::blaze_rs::core::ClPart::Str("]")
+            });
+        }
+        parts.push(quote! { ::blaze_rs::core::ClPart::Str(";\n") });
+    }
+
+    let name = ident.to_string();
+    let close = format!("}} {name};\ntypedef char __blaze_check_{name}_size[sizeof({name}) == ");
+    let align = format!(
+        " ? 1 : -1];\ntypedef struct {{ char c; {name} x; }} __blaze_align_{name};\ntypedef char __blaze_check_{name}_align[sizeof(__blaze_align_{name}) - sizeof({name}) == "
+    );
+    parts.push(quote! {
+        ::blaze_rs::core::ClPart::Str(#close),
+        ::blaze_rs::core::ClPart::Int(::core::mem::size_of::<#ident>()),
+        ::blaze_rs::core::ClPart::Str(#align),
+        ::blaze_rs::core::ClPart::Int(::core::mem::align_of::<#ident>()),
+        ::blaze_rs::core::ClPart::Str(" ? 1 : -1];\n")
+    });
+
+    Ok(quote! {
+        #[automatically_derived]
+        unsafe impl ::blaze_rs::core::KernelArg for #ident {
+            const CL_TYPE: &'static str = #name;
+            const CL_DEFINITION: &'static str = {
+                const PARTS: &[::blaze_rs::core::ClPart] = &[#(#parts),*];
+                const LEN: usize = ::blaze_rs::core::cl_len(PARTS);
+                const BYTES: &[u8; LEN] = &::blaze_rs::core::cl_concat::<LEN>(PARTS);
+                match ::core::str::from_utf8(BYTES) {
+                    Ok(x) => x,
+                    Err(_) => panic!("invalid OpenCL C definition"),
+                }
+            };
+        }
+    })
+}
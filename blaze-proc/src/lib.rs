@@ -9,7 +9,7 @@ macro_rules! flat_mod {
     }
 }
 
-use cl::Link;
+use cl::{Link, ProgramSource, SpecConstant, SpecConstants};
 use derive_syn_parse::Parse;
 use error::Error;
 use proc_macro2::{Ident, TokenStream};
@@ -24,28 +24,36 @@ use crate::cl::Blaze;
 mod cl;
 mod context;
 mod error;
+mod kernel_arg;
 mod num;
 mod utils;
 
-#[proc_macro_derive(NumOps, attributes(uninit))]
+#[proc_macro_derive(NumOps, attributes(uninit, pad))]
 pub fn derive_num_ops(items: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let items = parse_macro_input!(items as DeriveInput);
     num::derive_ops(items).into()
 }
 
-#[proc_macro_derive(NumOpsAssign, attributes(uninit))]
+#[proc_macro_derive(NumOpsAssign, attributes(uninit, pad))]
 pub fn derive_num_ops_assign(items: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let items = parse_macro_input!(items as DeriveInput);
     num::derive_ops_assign(items).into()
 }
 
+#[proc_macro_derive(KernelArg)]
+pub fn derive_kernel_arg(items: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let items = parse_macro_input!(items as DeriveInput);
+    kernel_arg::derive_kernel_arg(items).into()
+}
+
 #[proc_macro_attribute]
 pub fn global_context(
-    _attrs: proc_macro::TokenStream,
+    attrs: proc_macro::TokenStream,
     items: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
+    let attrs = parse_macro_input!(attrs as context::GlobalArgs);
     let items = parse_macro_input!(items as ItemStatic);
-    context::global_context(items).into()
+    context::global_context(attrs, items).into()
 }
 
 #[proc_macro]
@@ -162,16 +170,64 @@ pub fn blaze(
     let items = parse_macro_input!(items as Blaze);
 
     let mut inner = None;
+    let mut constants = None;
     for attr in &items.attrs {
-        if attr.path.is_ident(&format_ident!("link")) {
+        if attr.path.is_ident(&format_ident!("link"))
+            || attr.path.is_ident(&format_ident!("link_il"))
+        {
+            if inner.is_some() {
+                return syn::Error::new_spanned(
+                    attr,
+                    "only one `link` or `link_il` attribute can be specified",
+                )
+                .to_compile_error()
+                .into();
+            }
+
             let tokens = attr.tokens.clone().into();
             let link = parse_macro_input!(tokens as Link);
-            inner = Some(link.meta);
-            break;
+            inner = Some(match attr.path.is_ident(&format_ident!("link")) {
+                true => ProgramSource::Source(link.meta),
+                false => ProgramSource::Il(link.meta, None),
+            });
+        } else if attr.path.is_ident(&format_ident!("spec_constants")) {
+            if constants.is_some() {
+                return syn::Error::new_spanned(
+                    attr,
+                    "only one `spec_constants` attribute can be specified",
+                )
+                .to_compile_error()
+                .into();
+            }
+
+            let tokens = attr.tokens.clone().into();
+            let table = parse_macro_input!(tokens as SpecConstants);
+            if let Err(e) = table
+                .constants
+                .iter()
+                .try_for_each(SpecConstant::check_typed)
+            {
+                return e.to_compile_error().into();
+            }
+            constants = Some((attr, table.constants));
         }
     }
 
     if let Some(inner) = inner {
+        let inner = match (inner, constants) {
+            (ProgramSource::Il(il, _), Some((_, constants))) => {
+                ProgramSource::Il(il, Some(constants))
+            }
+            (ProgramSource::Source(_), Some((attr, _))) => {
+                return syn::Error::new_spanned(
+                    attr,
+                    "specialization constants are only supported by `link_il` programs",
+                )
+                .to_compile_error()
+                .into();
+            }
+            (inner, None) => inner,
+        };
         return cl::blaze_c(ident.vis, ident.ident, ident.generics, items, inner).into();
     }
 
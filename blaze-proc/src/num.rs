@@ -136,6 +136,12 @@ fn impl_field (field: &Field, idx: Option<usize>, path: &Path, fun: &Ident) -> T
         }
     }
 
+    if attrs.contains(&parse_quote! { #[pad] }) {
+        return quote! {
+            #ident #colon_token ::core::default::Default::default()
+        }
+    }
+
     let name = match ident {
         Some(x) => x.to_token_stream(),
         None => syn::Index::from(idx.unwrap_or_default()).to_token_stream()
@@ -151,7 +157,7 @@ fn impl_field (field: &Field, idx: Option<usize>, path: &Path, fun: &Ident) -> T
 fn impl_field_assign (field: &Field, idx: Option<usize>, path: &Path, fun: &Ident) -> TokenStream {
     let Field { attrs, ident, .. } = field; 
 
-    if attrs.contains(&parse_quote! { #[uninit] }) {
+    if attrs.contains(&parse_quote! { #[uninit] }) || attrs.contains(&parse_quote! { #[pad] }) {
         return TokenStream::new()
     }
 
@@ -134,13 +134,7 @@ impl<'a, T, C: Context + Clone> Iterator for ChunksExactMut<'a, T, C> {
         }
 
         let range = tri!(BufferRange::from_parts::<T>(self.offset, self.len));
-        let slice = unsafe {
-            tri!(BufMut::from_raw(
-                &self.buffer,
-                range,
-                self.buffer.context().clone()
-            ))
-        };
+        let slice = unsafe { tri!(BufMut::new_unchecked(self.buffer, range)) };
 
         self.offset = next_offset;
         return Some(Ok(slice));
@@ -162,13 +156,7 @@ impl<'a, T, C: Context + Clone> Iterator for ChunksExactMut<'a, T, C> {
             self.offset + n * self.len,
             self.len
         ));
-        let slice = unsafe {
-            tri!(BufMut::from_raw(
-                &self.buffer,
-                range,
-                self.buffer.context().clone()
-            ))
-        };
+        let slice = unsafe { tri!(BufMut::new_unchecked(self.buffer, range)) };
 
         self.offset = next_offset;
         return Some(Ok(slice));
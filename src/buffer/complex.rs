@@ -2,13 +2,13 @@ use super::{map::*, BufferRange, IntoRange};
 use crate::blaze_rs;
 use crate::buffer::{
     flags::{HostPtr, MemAccess, MemFlags},
-    RawBuffer,
+    Access, HazardTracker, HazardWait, RawBuffer,
 };
 use crate::core::*;
 use crate::{
     context::{local_scope, Context, Global, Scope},
     memobj::MapPtr,
-    prelude::Event,
+    prelude::{Event, RawEvent},
     WaitList,
 };
 use blaze_proc::join_various_blocking;
@@ -130,6 +130,7 @@ pub mod events {
 pub struct Buffer<T, C: Context = Global> {
     pub(super) inner: RawBuffer,
     pub(super) ctx: C,
+    pub(super) hazards: Option<Arc<HazardTracker>>,
     pub(super) phtm: PhantomData<T>,
 }
 
@@ -251,10 +252,39 @@ impl<T, C: Context> Buffer<T, C> {
         Ok(Self {
             inner,
             ctx,
+            hazards: None,
             phtm: PhantomData,
         })
     }
 
+    /// Enables [hazard tracking](HazardTracker) for this buffer.
+    /// Once enabled, every operation on the buffer (and on its slices) automatically waits for the previous operations it conflicts with.
+    #[inline]
+    pub fn track_hazards(&mut self) {
+        if self.hazards.is_none() {
+            self.hazards = Some(Arc::new(HazardTracker::new()));
+        }
+    }
+
+    /// Disables [hazard tracking](HazardTracker) for this buffer.
+    #[inline(always)]
+    pub fn untrack_hazards(&mut self) {
+        self.hazards = None;
+    }
+
+    /// Returns the buffer's [hazard tracker](HazardTracker), if tracking is enabled.
+    #[inline(always)]
+    pub fn hazard_tracker(&self) -> Option<&HazardTracker> {
+        self.hazards.as_deref()
+    }
+
+    #[inline]
+    pub(crate) fn record_hazard(&self, access: Access, event: &RawEvent) {
+        if let Some(ref hazards) = self.hazards {
+            hazards.record(access, event)
+        }
+    }
+
     /// Number of elements inside the buffer
     #[inline(always)]
     pub fn len(&self) -> Result<usize> {
@@ -304,6 +334,7 @@ impl<T, C: Context> Buffer<T, C> {
     /// Returns an iterator over mutable chunks of size `len` of the buffer, starting at `offset`.
     ///
     /// This is usefull for situations where you want to write to the same buffer in parallel, in regions that don't overlap.
+    /// Every chunk is a sub-buffer that can be passed to a different kernel launch. The byte offset of every chunk must be a multiple of [`sub_buffer_align`](RawBuffer::sub_buffer_align).
     #[docfg(feature = "cl1_1")]
     #[inline(always)]
     pub fn chunks_exact_mut(&mut self, offset: usize, len: usize) -> super::ChunksExactMut<'_, T, C>
@@ -326,6 +357,7 @@ impl<T, C: Context> Buffer<T, C> {
         Buffer {
             inner: self.inner,
             ctx: self.ctx,
+            hazards: self.hazards,
             phtm: PhantomData,
         }
     }
@@ -515,6 +547,8 @@ impl<T: 'static + Copy + Send + Sync, C: Context> Buffer<T, C> {
     /// Reads the contents of the buffer at the specified index, blocking the current thread until the operation has completed.
     pub fn get_blocking(&self, idx: usize, wait: WaitList) -> Result<T> {
         let mut result = MaybeUninit::<T>::uninit();
+        let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Read);
+        let wait = wait.list();
         let supplier = |queue| unsafe {
             self.inner.read_to_ptr_in(
                 BufferRange::new(idx * core::mem::size_of::<T>(), core::mem::size_of::<T>()),
@@ -550,6 +584,8 @@ impl<T: 'static + Copy + Send + Sync, C: Context> Buffer<T, C> {
         }
 
         let weak = Arc::downgrade(&result);
+        let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Read);
+        let wait = wait.list();
         let supplier = |queue| unsafe {
             self.inner.read_to_ptr_in(
                 BufferRange::new(idx * core::mem::size_of::<T>(), core::mem::size_of::<T>()),
@@ -565,6 +601,7 @@ impl<T: 'static + Copy + Send + Sync, C: Context> Buffer<T, C> {
         });
 
         evt.on_complete_silent(move |_, _| drop(weak))?;
+        self.record_hazard(Access::Read, &evt);
         return Ok(evt);
     }
 
@@ -584,6 +621,8 @@ impl<T: 'static + Copy + Send + Sync, C: Context> Buffer<T, C> {
         let vec = Arc::new(result);
         let weak = Arc::downgrade(&vec);
 
+        let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Read);
+        let wait = wait.list();
         let supplier = |queue| unsafe { self.inner.read_to_ptr_in(range, dst.cast(), queue, wait) };
 
         let evt = scope.enqueue(
@@ -595,6 +634,7 @@ impl<T: 'static + Copy + Send + Sync, C: Context> Buffer<T, C> {
         )?;
 
         evt.on_complete_silent(move |_, _| drop(weak))?;
+        self.record_hazard(Access::Read, &evt);
         return Ok(evt);
     }
 
@@ -603,6 +643,8 @@ impl<T: 'static + Copy + Send + Sync, C: Context> Buffer<T, C> {
         let range = range.into_range::<T>(&self.inner)?;
         let len = range.cb / core::mem::size_of::<T>();
         let mut result = Vec::<T>::with_capacity(len);
+        let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Read);
+        let wait = wait.list();
 
         let dst = Vec::as_mut_ptr(&mut result);
         let supplier = |queue| unsafe { self.inner.read_to_ptr_in(range, dst.cast(), queue, wait) };
@@ -615,6 +657,41 @@ impl<T: 'static + Copy + Send + Sync, C: Context> Buffer<T, C> {
         unsafe { self.ctx.next_queue().enqueue_unchecked(supplier, f)?.join() }
     }
 
+    /// Reads the contents of the buffer, without blocking the current thread.
+    /// The memory of the result is kept alive until the read has completed, even if the future is dropped before.
+    #[docfg(feature = "futures")]
+    pub async fn read_async<R: IntoRange>(&self, range: R, wait: WaitList<'_>) -> Result<Vec<T>> {
+        let range = range.into_range::<T>(&self.inner)?;
+        let len = range.cb / core::mem::size_of::<T>();
+
+        let evt = {
+            let mut result = Vec::<T>::with_capacity(len);
+            let dst = Vec::as_mut_ptr(&mut result);
+            let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Read);
+            let wait = wait.list();
+            let supplier =
+                |queue| unsafe { self.inner.read_to_ptr_in(range, dst.cast(), queue, wait) };
+
+            let vec = Arc::new(result);
+            let keep = vec.clone();
+            let evt = unsafe {
+                self.ctx.next_queue().enqueue_unchecked(
+                    supplier,
+                    BufferRead::<'_, T, C> {
+                        vec,
+                        _phtm: PhantomData,
+                    },
+                )?
+            };
+
+            evt.on_complete_silent(move |_, _| drop(keep))?;
+            evt
+        };
+
+        self.record_hazard(Access::Read, &evt);
+        return evt.join_async()?.await;
+    }
+
     /// Reads the contents of the buffer into `dst`.
     #[inline]
     pub fn read_into<'scope, 'env, O: Into<Option<usize>>>(
@@ -625,15 +702,16 @@ impl<T: 'static + Copy + Send + Sync, C: Context> Buffer<T, C> {
         wait: WaitList,
     ) -> Result<ReadIntoEvent<'scope, T, C>> {
         let range = BufferRange::from_parts::<T>(offset.into().unwrap_or_default(), dst.len())?;
+        let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Read);
+        let wait = wait.list();
         let supplier = |queue| unsafe {
             self.inner
                 .read_to_ptr_in(range, dst.as_mut_ptr().cast(), queue, wait)
         };
 
-        return Ok(Event::map_consumer(
-            s.enqueue_phantom(supplier)?,
-            BufferReadInto,
-        ));
+        let evt = s.enqueue_phantom(supplier)?;
+        self.record_hazard(Access::Read, &evt);
+        return Ok(Event::map_consumer(evt, BufferReadInto));
     }
 
     /// Reads the contents of the buffer into `dst`, blocking the current thread until the operation has completed.
@@ -645,6 +723,8 @@ impl<T: 'static + Copy + Send + Sync, C: Context> Buffer<T, C> {
         wait: WaitList,
     ) -> Result<()> {
         let range = BufferRange::from_parts::<T>(offset.into().unwrap_or_default(), dst.len())?;
+        let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Read);
+        let wait = wait.list();
         let supplier = |queue| unsafe {
             self.inner
                 .read_to_ptr_in(range, dst.as_mut_ptr().cast(), queue, wait)
@@ -664,15 +744,16 @@ impl<T: 'static + Copy + Send + Sync, C: Context> Buffer<T, C> {
     ) -> Result<WriteEvent<'scope, T, C>> {
         let range =
             BufferRange::from_parts::<T>(offset.into().unwrap_or_default(), src.len()).unwrap();
+        let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Write);
+        let wait = wait.list();
         let supplier = |queue| unsafe {
             self.inner
                 .write_from_ptr_in(range, src.as_ptr().cast(), queue, wait)
         };
 
-        return Ok(Event::map_consumer(
-            scope.enqueue_phantom(supplier)?,
-            BufferWrite,
-        ));
+        let evt = scope.enqueue_phantom(supplier)?;
+        self.record_hazard(Access::Write, &evt);
+        return Ok(Event::map_consumer(evt, BufferWrite));
     }
 
     /// Writes the contents of `src` into the buffer, blocking the current thread until the operation has completed.
@@ -685,6 +766,8 @@ impl<T: 'static + Copy + Send + Sync, C: Context> Buffer<T, C> {
     ) -> Result<()> {
         let range =
             BufferRange::from_parts::<T>(offset.into().unwrap_or_default(), src.len()).unwrap();
+        let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Write);
+        let wait = wait.list();
         let supplier = |queue| unsafe {
             self.inner
                 .write_from_ptr_in(range, src.as_ptr().cast(), queue, wait)
@@ -693,6 +776,34 @@ impl<T: 'static + Copy + Send + Sync, C: Context> Buffer<T, C> {
         self.ctx.next_queue().enqueue_noop(supplier)?.join()
     }
 
+    /// Writes the contents of `src` into the buffer, without blocking the current thread.
+    /// `src` is kept alive until the write has completed, even if the future is dropped before.
+    #[docfg(feature = "futures")]
+    pub async fn write_async(
+        &mut self,
+        offset: impl Into<Option<usize>>,
+        src: impl Into<Arc<[T]>>,
+        wait: WaitList<'_>,
+    ) -> Result<()> {
+        let src: Arc<[T]> = src.into();
+        let range = BufferRange::from_parts::<T>(offset.into().unwrap_or_default(), src.len())?;
+
+        let evt = {
+            let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Write);
+            let wait = wait.list();
+            let supplier = |queue| unsafe {
+                self.inner
+                    .write_from_ptr_in(range, src.as_ptr().cast(), queue, wait)
+            };
+
+            self.ctx.next_queue().enqueue_noop(supplier)?
+        };
+
+        evt.on_complete_silent(move |_, _| drop(src))?;
+        self.record_hazard(Access::Write, &evt);
+        return evt.join_async()?.await;
+    }
+
     /// Copies the contents from `self` to `dst`
     #[inline]
     pub fn copy_to<
@@ -727,13 +838,17 @@ impl<T: 'static + Copy + Send + Sync, C: Context> Buffer<T, C> {
             None => self.size()? - src_offset,
         };
 
+        let mut wait = HazardWait::new(wait)
+            .add(self.hazard_tracker(), Access::Read)
+            .add(dst.hazard_tracker(), Access::Write);
+        let wait = wait.list();
         let supplier =
             |queue| unsafe { dst.copy_from_in(dst_offset, &self, src_offset, size, queue, wait) };
 
-        return Ok(Event::map_consumer(
-            scope.enqueue_phantom(supplier)?,
-            BufferCopy,
-        ));
+        let evt = scope.enqueue_phantom(supplier)?;
+        self.record_hazard(Access::Read, &evt);
+        dst.record_hazard(Access::Write, &evt);
+        return Ok(Event::map_consumer(evt, BufferCopy));
     }
 
     /// Copies the contents from `self` to `dst`, blocking the current thread until the operation has completed.
@@ -763,12 +878,60 @@ impl<T: 'static + Copy + Send + Sync, C: Context> Buffer<T, C> {
             None => self.size()? - src_offset,
         };
 
+        let mut wait = HazardWait::new(wait)
+            .add(self.hazard_tracker(), Access::Read)
+            .add(dst.hazard_tracker(), Access::Write);
+        let wait = wait.list();
         let supplier =
             |queue| unsafe { dst.copy_from_in(dst_offset, &self, src_offset, size, queue, wait) };
 
         self.ctx.next_queue().enqueue_noop(supplier)?.join()
     }
 
+    /// Copies the contents from `self` to `dst`, without blocking the current thread.
+    #[docfg(feature = "futures")]
+    pub async fn copy_to_async(
+        &self,
+        src_offset: impl Into<Option<usize>>,
+        dst: &mut Self,
+        dst_offset: impl Into<Option<usize>>,
+        size: impl Into<Option<usize>>,
+        wait: WaitList<'_>,
+    ) -> Result<()> {
+        let src_offset = src_offset
+            .into()
+            .unwrap_or_default()
+            .checked_mul(core::mem::size_of::<T>())
+            .ok_or_else(|| Error::from_type(ErrorKind::InvalidValue))?;
+        let dst_offset = dst_offset
+            .into()
+            .unwrap_or_default()
+            .checked_mul(core::mem::size_of::<T>())
+            .ok_or_else(|| Error::from_type(ErrorKind::InvalidValue))?;
+        let size = match size.into() {
+            Some(x) => x
+                .checked_mul(core::mem::size_of::<T>())
+                .ok_or_else(|| Error::from_type(ErrorKind::InvalidValue))?,
+            None => self.size()? - src_offset,
+        };
+
+        let evt = {
+            let mut wait = HazardWait::new(wait)
+                .add(self.hazard_tracker(), Access::Read)
+                .add(dst.hazard_tracker(), Access::Write);
+            let wait = wait.list();
+            let supplier = |queue| unsafe {
+                dst.copy_from_in(dst_offset, &self, src_offset, size, queue, wait)
+            };
+
+            self.ctx.next_queue().enqueue_noop(supplier)?
+        };
+
+        self.record_hazard(Access::Read, &evt);
+        dst.record_hazard(Access::Write, &evt);
+        return evt.join_async_guarded()?.await;
+    }
+
     /// Copies the contents from `src` to `self`
     #[inline(always)]
     pub fn copy_from<
@@ -802,6 +965,21 @@ impl<T: 'static + Copy + Send + Sync, C: Context> Buffer<T, C> {
         src.copy_to_blocking(src_offset, self, dst_offset, size, wait)
     }
 
+    /// Copies the contents from `src` to `self`, without blocking the current thread.
+    #[docfg(feature = "futures")]
+    #[inline(always)]
+    pub async fn copy_from_async(
+        &mut self,
+        dst_offset: impl Into<Option<usize>>,
+        src: &Self,
+        src_offset: impl Into<Option<usize>>,
+        size: impl Into<Option<usize>>,
+        wait: WaitList<'_>,
+    ) -> Result<()> {
+        src.copy_to_async(src_offset, self, dst_offset, size, wait)
+            .await
+    }
+
     /// Fills a region of the buffer with `v`
     #[docfg(feature = "cl1_2")]
     #[inline(always)]
@@ -813,12 +991,13 @@ impl<T: 'static + Copy + Send + Sync, C: Context> Buffer<T, C> {
         wait: WaitList,
     ) -> Result<FillEvent<'scope, T, C>> {
         let range = range.into_range::<T>(&self.inner)?;
+        let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Write);
+        let wait = wait.list();
         let supplier = |queue| unsafe { self.inner.fill_raw_in(v, range, queue, wait) };
 
-        return Ok(Event::map_consumer(
-            scope.enqueue_phantom(supplier)?,
-            BufferFill,
-        ));
+        let evt = scope.enqueue_phantom(supplier)?;
+        self.record_hazard(Access::Write, &evt);
+        return Ok(Event::map_consumer(evt, BufferFill));
     }
 
     /// Fills a region of the buffer with `v`, blocking the current thread until the operation has completed.
@@ -826,6 +1005,8 @@ impl<T: 'static + Copy + Send + Sync, C: Context> Buffer<T, C> {
     #[inline(always)]
     pub fn fill_blocking<R: IntoRange>(&mut self, v: T, range: R, wait: WaitList) -> Result<()> {
         let range = range.into_range::<T>(&self.inner)?;
+        let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Write);
+        let wait = wait.list();
         let supplier = |queue| unsafe { self.inner.fill_raw_in(v, range, queue, wait) };
 
         self.ctx.next_queue().enqueue_noop(supplier)?.join()
@@ -842,6 +1023,8 @@ impl<T, C: Context> Buffer<T, C> {
         let range = range.into_range::<T>(&self.inner)?;
         let len = range.cb / core::mem::size_of::<T>();
         let mut ptr = MaybeUninit::uninit();
+        let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Read);
+        let wait = wait.list();
 
         let supplier = |queue| unsafe {
             let (_ptr, evt) = self.inner.map_read_in(range, queue, wait)?;
@@ -851,6 +1034,7 @@ impl<T, C: Context> Buffer<T, C> {
 
         unsafe {
             let noop = s.enqueue_noop(supplier)?;
+            self.record_hazard(Access::Read, &noop);
             let consumer = BufferMap::new(ptr.assume_init(), self, len);
             return Ok(noop.set_consumer(consumer));
         }
@@ -864,6 +1048,8 @@ impl<T, C: Context> Buffer<T, C> {
         let range = range.into_range::<T>(&self.inner)?;
         let len = range.cb / core::mem::size_of::<T>();
         let mut ptr = MaybeUninit::uninit();
+        let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Read);
+        let wait = wait.list();
         let supplier = |queue| unsafe {
             let (_ptr, evt) = self.inner.map_read_in(range, queue, wait)?;
             ptr.write(_ptr);
@@ -878,6 +1064,40 @@ impl<T, C: Context> Buffer<T, C> {
         }
     }
 
+    /// Maps the buffer for reading, without blocking the current thread.
+    #[docfg(feature = "futures")]
+    pub async fn map_async<R: IntoRange>(
+        &self,
+        range: R,
+        wait: WaitList<'_>,
+    ) -> Result<MapGuard<'_, T, C>> {
+        let range = range.into_range::<T>(&self.inner)?;
+        let len = range.cb / core::mem::size_of::<T>();
+
+        let (ptr, evt) = {
+            let mut ptr = MaybeUninit::uninit();
+            let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Read);
+            let wait = wait.list();
+            let supplier = |queue| unsafe {
+                let (_ptr, evt) = self.inner.map_read_in(range, queue, wait)?;
+                ptr.write(_ptr);
+                return Ok(evt);
+            };
+
+            let evt = self.ctx.next_queue().enqueue_noop(supplier)?;
+            (unsafe { ptr.assume_init() as usize }, evt)
+        };
+
+        self.record_hazard(Access::Read, &evt);
+
+        // the region is unmapped when `ptr` is dropped, even if the future is dropped or the map fails.
+        // `ptr` is dropped after the wait, which blocks until the map completes if the future is dropped early.
+        let ptr = core::ptr::slice_from_raw_parts_mut(ptr as *mut T, len);
+        let ptr = MapPtr::new(ptr, self.inner.clone().into(), &self.ctx);
+        evt.join_async_guarded()?.await?;
+        return Ok(MapGuard::new(ptr));
+    }
+
     pub fn map_mut<'scope, 'env, R: IntoRange>(
         &'env mut self,
         s: &'scope Scope<'scope, 'env, C>,
@@ -887,15 +1107,18 @@ impl<T, C: Context> Buffer<T, C> {
         let range = range.into_range::<T>(&self.inner)?;
         let len = range.cb / core::mem::size_of::<T>();
         let mut ptr = MaybeUninit::uninit();
+        let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Write);
+        let wait = wait.list();
 
         let supplier = |queue| unsafe {
-            let (_ptr, evt) = self.inner.map_read_in(range, queue, wait)?;
+            let (_ptr, evt) = self.inner.map_read_write_in(range, queue, wait)?;
             ptr.write(_ptr);
             return Ok(evt);
         };
 
         unsafe {
             let noop = s.enqueue_noop(supplier)?;
+            self.record_hazard(Access::Write, &noop);
             let consumer = BufferMapMut::new(ptr.assume_init(), self, len);
             return Ok(noop.set_consumer(consumer));
         }
@@ -909,8 +1132,10 @@ impl<T, C: Context> Buffer<T, C> {
         let range = range.into_range::<T>(&self.inner)?;
         let len = range.cb / core::mem::size_of::<T>();
         let mut ptr = MaybeUninit::uninit();
+        let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Write);
+        let wait = wait.list();
         let supplier = |queue| unsafe {
-            let (_ptr, evt) = self.inner.map_read_in(range, queue, wait)?;
+            let (_ptr, evt) = self.inner.map_read_write_in(range, queue, wait)?;
             ptr.write(_ptr);
             return Ok(evt);
         };
@@ -922,6 +1147,39 @@ impl<T, C: Context> Buffer<T, C> {
             return Ok(MapMutGuard::new(ptr));
         }
     }
+
+    /// Maps the buffer for writing, without blocking the current thread.
+    #[docfg(feature = "futures")]
+    pub async fn map_mut_async<R: IntoRange>(
+        &mut self,
+        range: R,
+        wait: WaitList<'_>,
+    ) -> Result<MapMutGuard<'_, T, C>> {
+        let range = range.into_range::<T>(&self.inner)?;
+        let len = range.cb / core::mem::size_of::<T>();
+
+        let (ptr, evt) = {
+            let mut ptr = MaybeUninit::uninit();
+            let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Write);
+            let wait = wait.list();
+            let supplier = |queue| unsafe {
+                let (_ptr, evt) = self.inner.map_read_write_in(range, queue, wait)?;
+                ptr.write(_ptr);
+                return Ok(evt);
+            };
+
+            let evt = self.ctx.next_queue().enqueue_noop(supplier)?;
+            (unsafe { ptr.assume_init() as usize }, evt)
+        };
+
+        self.record_hazard(Access::Write, &evt);
+
+        // see `map_async`
+        let ptr = core::ptr::slice_from_raw_parts_mut(ptr as *mut T, len);
+        let ptr = MapPtr::new(ptr, self.inner.clone().into(), &self.ctx);
+        evt.join_async_guarded()?.await?;
+        return Ok(MapMutGuard::new(ptr));
+    }
 }
 
 impl<T, C: Context> Deref for Buffer<T, C> {
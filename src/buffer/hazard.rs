@@ -0,0 +1,150 @@
+use crate::{
+    event::{EventStatus, RawEvent},
+    WaitList,
+};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Kind of access a command makes to a tracked buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// Tracks the last writer events and the outstanding reader events of a buffer, so that new commands can wait for
+/// them automatically, giving read-after-write, write-after-read and write-after-write correctness on out-of-order queues.
+///
+/// Hazard tracking is opt-in, and is enabled with [`track_hazards`](super::Buffer::track_hazards).
+/// Slices of a tracked buffer get their own tracker, which starts with the events of their parent and gives them back to it when dropped.
+#[derive(Debug, Default)]
+pub struct HazardTracker {
+    state: Mutex<HazardState>,
+    parent: Option<Arc<HazardTracker>>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct HazardState {
+    writes: Vec<RawEvent>,
+    reads: Vec<RawEvent>,
+}
+
+impl HazardTracker {
+    /// Creates a new, empty tracker.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new tracker for a slice of the buffer tracked by `parent`.
+    #[inline]
+    pub fn child(parent: &Arc<HazardTracker>) -> Self {
+        let state = parent.lock().clone();
+        Self {
+            state: Mutex::new(state),
+            parent: Some(parent.clone()),
+        }
+    }
+
+    /// Adds the events that a command with the specified access must wait for into `wait`.
+    #[inline]
+    pub fn hazards(&self, access: Access, wait: &mut Vec<RawEvent>) {
+        let state = self.lock();
+        wait.extend(state.writes.iter().cloned());
+        if access == Access::Write {
+            wait.extend(state.reads.iter().cloned());
+        }
+    }
+
+    /// Registers `event` as a command with the specified access.
+    #[inline]
+    pub fn record(&self, access: Access, event: &RawEvent) {
+        let mut state = self.lock();
+        match access {
+            Access::Read => {
+                state.reads.retain(is_pending);
+                state.reads.push(event.clone());
+            }
+
+            Access::Write => {
+                state.reads.clear();
+                state.writes.clear();
+                state.writes.push(event.clone());
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn lock(&self) -> MutexGuard<'_, HazardState> {
+        match self.state.lock() {
+            Ok(x) => x,
+            Err(e) => e.into_inner(),
+        }
+    }
+}
+
+impl Drop for HazardTracker {
+    fn drop(&mut self) {
+        if let Some(ref parent) = self.parent {
+            let state = match self.state.get_mut() {
+                Ok(x) => core::mem::take(x),
+                Err(e) => core::mem::take(e.into_inner()),
+            };
+
+            let mut parent = parent.lock();
+            parent.writes.retain(is_pending);
+            parent.reads.retain(is_pending);
+            for evt in state.writes.into_iter().filter(is_pending) {
+                if !parent.writes.contains(&evt) {
+                    parent.writes.push(evt)
+                }
+            }
+            for evt in state.reads.into_iter().filter(is_pending) {
+                if !parent.reads.contains(&evt) {
+                    parent.reads.push(evt)
+                }
+            }
+        }
+    }
+}
+
+/// Wait list extended with the hazards of the buffers used by a command.
+pub(crate) struct HazardWait<'a> {
+    user: WaitList<'a>,
+    events: Vec<RawEvent>,
+}
+
+impl<'a> HazardWait<'a> {
+    #[inline(always)]
+    pub fn new(user: WaitList<'a>) -> Self {
+        Self {
+            user,
+            events: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn add(mut self, tracker: Option<&HazardTracker>, access: Access) -> Self {
+        if let Some(tracker) = tracker {
+            tracker.hazards(access, &mut self.events);
+        }
+        self
+    }
+
+    /// Returns the final wait list
+    #[inline]
+    pub fn list(&mut self) -> WaitList<'_> {
+        if self.events.is_empty() {
+            return self.user;
+        }
+
+        if let Some(user) = self.user.take() {
+            self.events.extend(user.iter().cloned());
+        }
+        Some(&self.events)
+    }
+}
+
+#[inline]
+fn is_pending(evt: &RawEvent) -> bool {
+    !matches!(evt.status(), Ok(EventStatus::Complete) | Err(_))
+}
@@ -1,10 +1,10 @@
-flat_mod!(raw, complex, range);
+flat_mod!(raw, complex, range, hazard, vec);
 pub mod map;
 
 #[cfg(feature = "cl1_1")]
 flat_mod!(slice, chunks);
 
-use crate::prelude::{Context, RawEvent, RawKernel, Result};
+use crate::prelude::{Context, Error, ErrorKind, RawEvent, RawKernel, Result};
 use blaze_proc::docfg;
 
 #[cfg(feature = "svm")]
@@ -21,23 +21,95 @@ pub unsafe trait KernelPointer<T: Sync> {
         idx: u32,
     ) -> Result<()>;
     fn complete(&self, event: &RawEvent) -> Result<()>;
+
+    /// Sets the pointer as an argument the kernel may write to. By default, this is the same as [`set_arg`](KernelPointer::set_arg).
+    #[inline(always)]
+    unsafe fn set_arg_mut(
+        &self,
+        kernel: &mut RawKernel,
+        wait: &mut Vec<RawEvent>,
+        idx: u32,
+    ) -> Result<()> {
+        self.set_arg(kernel, wait, idx)
+    }
+
+    /// Completes an argument set with [`set_arg_mut`](KernelPointer::set_arg_mut). By default, this is the same as [`complete`](KernelPointer::complete).
+    #[inline(always)]
+    fn complete_mut(&self, event: &RawEvent) -> Result<()> {
+        self.complete(event)
+    }
+
+    /// Returns the number of elements the kernel may access through the pointer, used to check the extents declared with `#[extent]`.
+    /// By default, the extent is unknown, so any declared extent fails to check.
+    #[inline(always)]
+    fn extent(&self) -> Result<usize> {
+        Err(Error::new(
+            ErrorKind::InvalidOperation,
+            "the extent of the pointer is unknown",
+        ))
+    }
+}
+
+/// Checks that a kernel doesn't access more than `extent` elements of the argument `name`. Used by the kernels with `#[extent]` attributes.
+#[doc(hidden)]
+#[inline]
+pub fn check_extent<N: TryInto<usize>>(name: &str, extent: usize, accessed: N) -> Result<()> {
+    match accessed.try_into() {
+        Ok(accessed) if accessed <= extent => Ok(()),
+        Ok(accessed) => Err(Error::new(
+            ErrorKind::InvalidArgValue,
+            format!("kernel may access {accessed} elements of `{name}`, but it only has {extent}"),
+        )),
+        Err(_) => Err(Error::new(
+            ErrorKind::InvalidArgValue,
+            format!("extent of `{name}` isn't a valid size"),
+        )),
+    }
 }
 
 unsafe impl<T: Copy + Sync, C: Context> KernelPointer<T> for Buffer<T, C> {
-    #[inline(always)]
+    #[inline]
     unsafe fn set_arg(
         &self,
         kernel: &mut RawKernel,
-        _wait: &mut Vec<RawEvent>,
+        wait: &mut Vec<RawEvent>,
+        idx: u32,
+    ) -> Result<()> {
+        if let Some(hazards) = self.hazard_tracker() {
+            hazards.hazards(Access::Read, wait);
+        }
+        kernel.set_argument::<opencl_sys::cl_mem, _>(idx, self.id_ref())
+    }
+
+    #[inline(always)]
+    fn complete(&self, event: &RawEvent) -> Result<()> {
+        self.record_hazard(Access::Read, event);
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn set_arg_mut(
+        &self,
+        kernel: &mut RawKernel,
+        wait: &mut Vec<RawEvent>,
         idx: u32,
     ) -> Result<()> {
+        if let Some(hazards) = self.hazard_tracker() {
+            hazards.hazards(Access::Write, wait);
+        }
         kernel.set_argument::<opencl_sys::cl_mem, _>(idx, self.id_ref())
     }
 
     #[inline(always)]
-    fn complete(&self, _event: &RawEvent) -> Result<()> {
+    fn complete_mut(&self, event: &RawEvent) -> Result<()> {
+        self.record_hazard(Access::Write, event);
         Ok(())
     }
+
+    #[inline(always)]
+    fn extent(&self) -> Result<usize> {
+        self.len()
+    }
 }
 
 unsafe impl<T: Copy + Sync, C: Context> KernelPointer<T> for rect::RectBuffer2D<T, C> {
@@ -45,15 +117,159 @@ unsafe impl<T: Copy + Sync, C: Context> KernelPointer<T> for rect::RectBuffer2D<
     unsafe fn set_arg(
         &self,
         kernel: &mut RawKernel,
+        wait: &mut Vec<RawEvent>,
+        idx: u32,
+    ) -> Result<()> {
+        KernelPointer::<T>::set_arg(self.as_flat(), kernel, wait, idx)
+    }
+
+    #[inline(always)]
+    fn complete(&self, event: &RawEvent) -> Result<()> {
+        KernelPointer::<T>::complete(self.as_flat(), event)
+    }
+
+    #[inline(always)]
+    unsafe fn set_arg_mut(
+        &self,
+        kernel: &mut RawKernel,
+        wait: &mut Vec<RawEvent>,
+        idx: u32,
+    ) -> Result<()> {
+        KernelPointer::<T>::set_arg_mut(self.as_flat(), kernel, wait, idx)
+    }
+
+    #[inline(always)]
+    fn complete_mut(&self, event: &RawEvent) -> Result<()> {
+        KernelPointer::<T>::complete_mut(self.as_flat(), event)
+    }
+
+    #[inline(always)]
+    fn extent(&self) -> Result<usize> {
+        KernelPointer::<T>::extent(self.as_flat())
+    }
+}
+
+#[docfg(feature = "cl1_1")]
+unsafe impl<T: Copy + Sync, C: Context> KernelPointer<T> for Buf<'_, T, C> {
+    #[inline(always)]
+    unsafe fn set_arg(
+        &self,
+        kernel: &mut RawKernel,
+        wait: &mut Vec<RawEvent>,
+        idx: u32,
+    ) -> Result<()> {
+        KernelPointer::<T>::set_arg(&**self, kernel, wait, idx)
+    }
+
+    #[inline(always)]
+    fn complete(&self, event: &RawEvent) -> Result<()> {
+        KernelPointer::<T>::complete(&**self, event)
+    }
+
+    /// Immutable slices are created as read-only sub-buffers, so kernels cannot write to them.
+    #[inline]
+    unsafe fn set_arg_mut(
+        &self,
+        _kernel: &mut RawKernel,
         _wait: &mut Vec<RawEvent>,
+        _idx: u32,
+    ) -> Result<()> {
+        Err(crate::prelude::Error::new(
+            crate::prelude::ErrorKind::InvalidMemObject,
+            "immutable slices cannot be passed as mutable kernel arguments",
+        ))
+    }
+
+    #[inline(always)]
+    fn extent(&self) -> Result<usize> {
+        KernelPointer::<T>::extent(&**self)
+    }
+}
+
+#[docfg(feature = "cl1_1")]
+unsafe impl<T: Copy + Sync, C: Context> KernelPointer<T> for BufMut<'_, T, C> {
+    #[inline(always)]
+    unsafe fn set_arg(
+        &self,
+        kernel: &mut RawKernel,
+        wait: &mut Vec<RawEvent>,
         idx: u32,
     ) -> Result<()> {
-        kernel.set_argument::<opencl_sys::cl_mem, _>(idx, self.id_ref())
+        KernelPointer::<T>::set_arg(&**self, kernel, wait, idx)
     }
 
     #[inline(always)]
-    fn complete(&self, _event: &RawEvent) -> Result<()> {
-        Ok(())
+    fn complete(&self, event: &RawEvent) -> Result<()> {
+        KernelPointer::<T>::complete(&**self, event)
+    }
+
+    #[inline(always)]
+    unsafe fn set_arg_mut(
+        &self,
+        kernel: &mut RawKernel,
+        wait: &mut Vec<RawEvent>,
+        idx: u32,
+    ) -> Result<()> {
+        KernelPointer::<T>::set_arg_mut(&**self, kernel, wait, idx)
+    }
+
+    #[inline(always)]
+    fn complete_mut(&self, event: &RawEvent) -> Result<()> {
+        KernelPointer::<T>::complete_mut(&**self, event)
+    }
+
+    #[inline(always)]
+    fn extent(&self) -> Result<usize> {
+        KernelPointer::<T>::extent(&**self)
+    }
+}
+
+unsafe impl<T: Copy + Sync, C: Context> KernelPointer<T> for BufferVec<T, C> {
+    #[inline]
+    unsafe fn set_arg(
+        &self,
+        kernel: &mut RawKernel,
+        wait: &mut Vec<RawEvent>,
+        idx: u32,
+    ) -> Result<()> {
+        match self.as_buffer() {
+            Some(inner) => KernelPointer::<T>::set_arg(inner, kernel, wait, idx),
+            None => kernel.set_argument::<opencl_sys::cl_mem, _>(idx, core::ptr::null_mut()),
+        }
+    }
+
+    #[inline]
+    fn complete(&self, event: &RawEvent) -> Result<()> {
+        match self.as_buffer() {
+            Some(inner) => KernelPointer::<T>::complete(inner, event),
+            None => Ok(()),
+        }
+    }
+
+    #[inline]
+    unsafe fn set_arg_mut(
+        &self,
+        kernel: &mut RawKernel,
+        wait: &mut Vec<RawEvent>,
+        idx: u32,
+    ) -> Result<()> {
+        match self.as_buffer() {
+            Some(inner) => KernelPointer::<T>::set_arg_mut(inner, kernel, wait, idx),
+            None => kernel.set_argument::<opencl_sys::cl_mem, _>(idx, core::ptr::null_mut()),
+        }
+    }
+
+    #[inline]
+    fn complete_mut(&self, event: &RawEvent) -> Result<()> {
+        match self.as_buffer() {
+            Some(inner) => KernelPointer::<T>::complete_mut(inner, event),
+            None => Ok(()),
+        }
+    }
+
+    #[inline(always)]
+    fn extent(&self) -> Result<usize> {
+        Ok(self.len())
     }
 }
 
@@ -94,6 +310,11 @@ unsafe impl<T: Sync, C: Context> KernelPointer<T> for SvmBox<[T], C> {
 
         Ok(())
     }
+
+    #[inline(always)]
+    fn extent(&self) -> Result<usize> {
+        Ok(SvmPointer::<T>::len(self))
+    }
 }
 
 #[docfg(feature = "svm")]
@@ -172,6 +393,11 @@ unsafe impl<T: Sync, C: Context> KernelPointer<T> for SvmVec<T, C> {
 
         Ok(())
     }
+
+    #[inline(always)]
+    fn extent(&self) -> Result<usize> {
+        Ok(SvmPointer::<T>::len(self))
+    }
 }
 
 /*
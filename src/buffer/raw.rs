@@ -49,10 +49,27 @@ impl RawBuffer {
         RawMemObject::from_id(id).map(Self)
     }
 
+    /// Returns the alignment in bytes required for the offset of the buffer's sub-buffers, which is the largest base address alignment of the context's devices.
+    #[docfg(feature = "cl1_1")]
+    pub fn sub_buffer_align (&self) -> Result<usize> {
+        let mut align = 1;
+        for device in self.context()?.devices()? {
+            align = align.max(device.mem_base_addr_align()? as usize / 8);
+        }
+        Ok(align)
+    }
+
     /// Creates a new buffer object (referred to as a sub-buffer object) from an existing buffer object.
     #[docfg(feature = "cl1_1")]
     pub unsafe fn create_sub_buffer (&self, flags: super::flags::MemAccess, region: BufferRange) -> Result<RawBuffer> {
         let BufferRange { offset, cb } = region;
+        if offset != 0 {
+            let align = self.sub_buffer_align()?;
+            if offset % align != 0 {
+                return Err(Error::new(ErrorKind::MisalignedSubBufferOffset, format!("sub-buffer offset of {offset} bytes isn't a multiple of the {align} byte alignment of the context's devices")))
+            }
+        }
+
         let region = opencl_sys::cl_buffer_region { origin: offset, size: cb };
 
         let mut err = 0;
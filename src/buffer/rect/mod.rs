@@ -142,7 +142,7 @@ impl<T, C: Context> RectBuffer2D<T, C> {
 }
 
 #[cfg(feature = "cl1_1")]
-use crate::{WaitList, memobj::IntoRange2D};
+use crate::{WaitList, memobj::IntoRange2D, buffer::{Access, HazardWait}};
 
 #[docfg(feature = "cl1_1")]
 impl<T: Copy, C: Context> RectBuffer2D<T, C> {
@@ -154,6 +154,8 @@ impl<T: Copy, C: Context> RectBuffer2D<T, C> {
         let mut dst = Rect2D::<T>::try_new_uninit(range.width(), range.height())
             .map_err(|e| Error::new(ErrorKind::OutOfHostMemory, e))?;
 
+        let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Read);
+        let wait = wait.list();
         let supplier = |queue| unsafe {
             self.read_rect_to_ptr_in(
                 buffer_origin, [0; 3], region,
@@ -163,10 +165,9 @@ impl<T: Copy, C: Context> RectBuffer2D<T, C> {
             )
         };
 
-        return Ok(scope
-            .enqueue_noop(supplier)?
-            .set_consumer(ReadRect(dst, PhantomData))
-        )
+        let evt = scope.enqueue_noop(supplier)?;
+        self.record_hazard(Access::Read, &evt);
+        return Ok(evt.set_consumer(ReadRect(dst, PhantomData)))
     }
     
     pub fn read_blocking<R: IntoRange2D> (&self, range: R, wait: WaitList) -> Result<RectBox2D<T>> {
@@ -177,6 +178,8 @@ impl<T: Copy, C: Context> RectBuffer2D<T, C> {
         let mut dst = Rect2D::<T>::try_new_uninit(range.width(), range.height())
             .map_err(|e| Error::new(ErrorKind::OutOfHostMemory, e))?;
 
+        let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Read);
+        let wait = wait.list();
         let supplier = |queue| unsafe {
             self.read_rect_to_ptr_in(
                 buffer_origin, [0; 3], region,
@@ -209,6 +212,8 @@ impl<T: Copy, C: Context> RectBuffer2D<T, C> {
             None => [host_row_pitch - host_origin[0], (src.0.len() / src.1) - host_origin[1], 1]
         };
 
+        let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Write);
+        let wait = wait.list();
         let supplier = |queue| unsafe {
             self.write_rect_from_ptr_in(
                 buffer_origin, host_origin, region,
@@ -218,7 +223,9 @@ impl<T: Copy, C: Context> RectBuffer2D<T, C> {
             )
         };
 
-        return Ok(Event::map_consumer(scope.enqueue_phantom(supplier)?, RectBuffer2DWrite))
+        let evt = scope.enqueue_phantom(supplier)?;
+        self.record_hazard(Access::Write, &evt);
+        return Ok(Event::map_consumer(evt, RectBuffer2DWrite))
     }
 
     pub fn write_blocking (&mut self, offset_dst: impl Into<Option<[usize; 2]>>, src: (&[T], usize), offset_src: impl Into<Option<[usize; 2]>>, region: impl Into<Option<[usize; 2]>>, wait: WaitList) -> Result<()> {
@@ -241,6 +248,8 @@ impl<T: Copy, C: Context> RectBuffer2D<T, C> {
         };
 
         let queue = self.context().next_queue().clone();
+        let mut wait = HazardWait::new(wait).add(self.hazard_tracker(), Access::Write);
+        let wait = wait.list();
         let supplier = |queue| unsafe {
             self.write_rect_from_ptr_in(
                 buffer_origin, host_origin, region,
@@ -268,6 +277,8 @@ impl<T: Copy, C: Context> RectBuffer2D<T, C> {
             None => [src_row_pitch - src_origin[0], src.height()? - src_origin[1], 1]
         };
 
+        let mut wait = HazardWait::new(wait).add(src.hazard_tracker(), Access::Read).add(self.hazard_tracker(), Access::Write);
+        let wait = wait.list();
         let supplier = |queue| unsafe {
             self.copy_from_rect_raw_in(
                 dst_origin, src_origin, region,
@@ -277,7 +288,10 @@ impl<T: Copy, C: Context> RectBuffer2D<T, C> {
             )
         };
 
-        return Ok(Event::map_consumer(scope.enqueue_phantom(supplier)?, RectBuffer2DCopy))
+        let evt = scope.enqueue_phantom(supplier)?;
+        src.record_hazard(Access::Read, &evt);
+        self.record_hazard(Access::Write, &evt);
+        return Ok(Event::map_consumer(evt, RectBuffer2DCopy))
     }
 
     pub fn copy_from_blocking (&mut self, offset_dst: impl Into<Option<[usize; 2]>>, src: &Self, offset_src: impl Into<Option<[usize; 2]>>, region: impl Into<Option<[usize; 2]>>, wait: WaitList) -> Result<()> {
@@ -295,6 +309,8 @@ impl<T: Copy, C: Context> RectBuffer2D<T, C> {
             None => [src_row_pitch - src_origin[0], src.height()? - src_origin[1], 1]
         };
 
+        let mut wait = HazardWait::new(wait).add(src.hazard_tracker(), Access::Read).add(self.hazard_tracker(), Access::Write);
+        let wait = wait.list();
         let supplier = |queue| unsafe {
             self.copy_from_rect_raw_in(
                 dst_origin, src_origin, region,
@@ -1,9 +1,10 @@
-use super::IntoRange;
+use super::{HazardTracker, IntoRange};
 use crate::prelude::*;
 use std::{
     fmt::Debug,
     marker::PhantomData,
     ops::{Deref, DerefMut},
+    sync::Arc,
 };
 
 /// An immutable slice of a [`Buffer`]
@@ -34,6 +35,7 @@ impl<'a, T, C: Context> Buf<'a, T, C> {
             inner: Buffer {
                 inner,
                 ctx: parent.ctx.clone(),
+                hazards: child_hazards(parent),
                 phtm: PhantomData,
             },
             phtm: PhantomData,
@@ -56,6 +58,7 @@ impl<'a, T, C: Context> Buf<'a, T, C> {
             inner: Buffer {
                 inner,
                 ctx,
+                hazards: None,
                 phtm: PhantomData,
             },
             phtm: PhantomData,
@@ -99,6 +102,18 @@ pub struct BufMut<'a, T, C: Context = Global> {
 impl<'a, T, C: Context> BufMut<'a, T, C> {
     #[inline]
     pub fn new<R: IntoRange>(parent: &'a mut Buffer<T, C>, range: R) -> Result<Self>
+    where
+        C: Clone,
+    {
+        unsafe { Self::new_unchecked(parent, range) }
+    }
+
+    /// Creates a mutable slice without borrowing the parent mutably. The caller must ensure that the slices don't overlap.
+    #[inline]
+    pub(super) unsafe fn new_unchecked<R: IntoRange>(
+        parent: &Buffer<T, C>,
+        range: R,
+    ) -> Result<Self>
     where
         C: Clone,
     {
@@ -116,6 +131,7 @@ impl<'a, T, C: Context> BufMut<'a, T, C> {
             inner: Buffer {
                 inner,
                 ctx: parent.ctx.clone(),
+                hazards: child_hazards(parent),
                 phtm: PhantomData,
             },
             phtm: PhantomData,
@@ -138,6 +154,7 @@ impl<'a, T, C: Context> BufMut<'a, T, C> {
             inner: Buffer {
                 inner,
                 ctx,
+                hazards: None,
                 phtm: PhantomData,
             },
             phtm: PhantomData,
@@ -176,3 +193,11 @@ impl<T: PartialEq, C: Context> PartialEq for BufMut<'_, T, C> {
 }
 
 impl<T: Eq, C: Context> Eq for BufMut<'_, T, C> {}
+
+#[inline]
+fn child_hazards<T, C: Context>(parent: &Buffer<T, C>) -> Option<Arc<HazardTracker>> {
+    parent
+        .hazards
+        .as_ref()
+        .map(|hazards| Arc::new(HazardTracker::child(hazards)))
+}
@@ -0,0 +1,246 @@
+use super::{
+    flags::{HostPtr, MemAccess, MemFlags},
+    Buffer,
+};
+use crate::{
+    context::{Context, Global},
+    core::*,
+    WaitList,
+};
+use blaze_proc::docfg;
+use std::fmt::Debug;
+
+/// Minimum capacity allocated by a growing [`BufferVec`].
+const MIN_CAPACITY: usize = 4;
+
+/// A growable device vector.
+///
+/// The elements are stored in a [`Buffer`] with space for [`capacity`](BufferVec::capacity) elements, of which only the first [`len`](BufferVec::len) are live.
+/// When the vector runs out of space, a new buffer is allocated and the live elements are copied into it on the device.
+///
+/// The vector doesn't implement [`Deref`](std::ops::Deref), since a view of its live elements is a sub-buffer, whose creation can fail
+/// (OpenCL doesn't support empty sub-buffers). Views are created with [`as_buf`](BufferVec::as_buf) and [`as_buf_mut`](BufferVec::as_buf_mut) instead.
+pub struct BufferVec<T: Copy, C: Context = Global> {
+    inner: Option<Buffer<T, C>>,
+    len: usize,
+    ctx: C,
+    flags: MemFlags,
+}
+
+impl<T: 'static + Copy + Send + Sync> BufferVec<T> {
+    /// Creates a new, empty vector. No device memory is allocated until elements are added.
+    #[inline(always)]
+    pub fn new(access: MemAccess) -> Self {
+        Self::new_in(Global, access)
+    }
+
+    /// Creates a new, empty vector with space for at least `capacity` elements.
+    #[inline(always)]
+    pub fn with_capacity(capacity: usize, access: MemAccess) -> Result<Self> {
+        Self::with_capacity_in(Global, capacity, access)
+    }
+}
+
+impl<T: Copy, C: Context> BufferVec<T, C> {
+    /// Creates a new, empty vector. No device memory is allocated until elements are added.
+    #[inline(always)]
+    pub fn new_in(ctx: C, access: MemAccess) -> Self {
+        Self {
+            inner: None,
+            len: 0,
+            ctx,
+            flags: MemFlags::new(access, HostPtr::default()),
+        }
+    }
+
+    /// Number of live elements of the vector.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the vector has no live elements.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of elements the vector can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> Result<usize> {
+        match self.inner {
+            Some(ref inner) => inner.len(),
+            None => Ok(0),
+        }
+    }
+
+    /// Returns the vector's context.
+    #[inline(always)]
+    pub fn context(&self) -> &C {
+        &self.ctx
+    }
+
+    /// Returns the buffer with the vector's whole allocation, if any. Only the first [`len`](BufferVec::len) elements of the buffer are live.
+    #[inline(always)]
+    pub fn as_buffer(&self) -> Option<&Buffer<T, C>> {
+        self.inner.as_ref()
+    }
+
+    /// Shortens the vector to `len` elements. Has no effect if the vector is already shorter.
+    #[inline(always)]
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len)
+    }
+
+    /// Removes all the elements of the vector, keeping its allocation.
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.len = 0
+    }
+}
+
+impl<T: 'static + Copy + Send + Sync, C: Context + Clone> BufferVec<T, C> {
+    /// Creates a new, empty vector with space for at least `capacity` elements.
+    #[inline]
+    pub fn with_capacity_in(ctx: C, capacity: usize, access: MemAccess) -> Result<Self> {
+        let mut result = Self::new_in(ctx, access);
+        result.reserve(capacity)?;
+        return Ok(result);
+    }
+
+    /// Reserves space for at least `additional` more elements, growing the allocation geometrically.
+    #[inline(always)]
+    pub fn reserve(&mut self, additional: usize) -> Result<()> {
+        self.reserve_after(additional, None)
+    }
+
+    /// Reserves space for at least `additional` more elements. If the vector is reallocated, the copy of its elements waits for `wait`.
+    fn reserve_after(&mut self, additional: usize, wait: WaitList) -> Result<()> {
+        let required = self
+            .len
+            .checked_add(additional)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidBufferSize, "capacity overflow"))?;
+
+        let capacity = self.capacity()?;
+        if required <= capacity {
+            return Ok(());
+        }
+
+        let capacity = required.max(capacity.saturating_mul(2)).max(MIN_CAPACITY);
+        self.reallocate(capacity, wait)
+    }
+
+    /// Shrinks the allocation to fit the live elements, freeing it if the vector is empty.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) -> Result<()> {
+        if self.capacity()? > self.len {
+            return self.reallocate(self.len, None);
+        }
+        Ok(())
+    }
+
+    /// Appends the elements of `v` to the end of the vector, blocking the current thread until the write has completed.
+    pub fn push_slice(&mut self, v: &[T], wait: WaitList) -> Result<()> {
+        if v.is_empty() {
+            return Ok(());
+        }
+
+        self.reserve_after(v.len(), wait)?;
+        let inner = self.inner.as_mut().unwrap();
+        inner.write_blocking(self.len, v, wait)?;
+
+        self.len += v.len();
+        return Ok(());
+    }
+
+    /// Appends the contents of `src` to the end of the vector, copying them on the device.
+    /// Blocks the current thread until the copy has completed.
+    pub fn extend_from_buffer(&mut self, src: &Buffer<T, C>, wait: WaitList) -> Result<()> {
+        let len = src.len()?;
+        if len == 0 {
+            return Ok(());
+        }
+
+        self.reserve_after(len, wait)?;
+        let inner = self.inner.as_mut().unwrap();
+        inner.copy_from_blocking(self.len, src, 0, len, wait)?;
+
+        self.len += len;
+        return Ok(());
+    }
+
+    /// Reads the live elements of the vector, blocking the current thread until the operation has completed.
+    pub fn read_blocking(&self, wait: WaitList) -> Result<Vec<T>> {
+        match self.inner {
+            Some(ref inner) if self.len > 0 => inner.read_blocking(..self.len, wait),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Returns a view of the live elements of the vector.
+    /// Returns an error if the vector is empty, since OpenCL doesn't support empty sub-buffers.
+    #[docfg(feature = "cl1_1")]
+    #[inline]
+    pub fn as_buf(&self) -> Result<super::Buf<'_, T, C>> {
+        match self.inner {
+            Some(ref inner) if self.len > 0 => inner.slice(..self.len),
+            _ => Err(Error::new(
+                ErrorKind::InvalidBufferSize,
+                "cannot create a view of an empty vector",
+            )),
+        }
+    }
+
+    /// Returns a mutable view of the live elements of the vector.
+    /// Returns an error if the vector is empty, since OpenCL doesn't support empty sub-buffers.
+    #[docfg(feature = "cl1_1")]
+    #[inline]
+    pub fn as_buf_mut(&mut self) -> Result<super::BufMut<'_, T, C>> {
+        match self.inner {
+            Some(ref mut inner) if self.len > 0 => inner.slice_mut(..self.len),
+            _ => Err(Error::new(
+                ErrorKind::InvalidBufferSize,
+                "cannot create a view of an empty vector",
+            )),
+        }
+    }
+
+    /// Moves the live elements into a new allocation with space for `capacity` elements, once the events of `wait` have completed.
+    fn reallocate(&mut self, capacity: usize, wait: WaitList) -> Result<()> {
+        debug_assert!(capacity >= self.len);
+        let mut new = match capacity {
+            0 => None,
+            _ => unsafe {
+                Some(Buffer::create_in(
+                    self.ctx.clone(),
+                    capacity,
+                    self.flags,
+                    None,
+                )?)
+            },
+        };
+
+        if let (Some(old), Some(new)) = (self.inner.as_ref(), new.as_mut()) {
+            if old.hazard_tracker().is_some() {
+                new.track_hazards();
+            }
+
+            if self.len > 0 {
+                new.copy_from_blocking(0, old, 0, self.len, wait)?;
+            }
+        }
+
+        self.inner = new;
+        return Ok(());
+    }
+}
+
+impl<T: Copy, C: Context> Debug for BufferVec<T, C> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BufferVec")
+            .field("len", &self.len)
+            .field("inner", &self.inner.as_ref().map(|x| x.id()))
+            .finish()
+    }
+}
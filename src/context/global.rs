@@ -1,15 +1,72 @@
-use std::ops::Deref;
-use super::{Context, RawContext, CommandQueue};
+use std::{ops::Deref, marker::PhantomData, fmt::Debug};
+use once_cell::sync::Lazy;
+use crate::core::Specializations;
+use super::{Context, RawContext, CommandQueue, SimpleContext};
 
-extern "Rust" {
-    fn __blaze__global__as_raw () -> &'static RawContext;
-    fn __blaze__global__queues () -> &'static [CommandQueue];
-    fn __blaze__global__next_queue () -> &'static CommandQueue;
+/// Default global contexts declared with [`global_context`](crate::macros::global_context).
+/// At most one may be declared, which is checked when building (every declaration also exports the same symbol) and on first use.
+#[doc(hidden)]
+#[linkme::distributed_slice]
+pub static GLOBAL_CONTEXTS: [fn() -> &'static (dyn Context + Sync)];
+
+/// Marker type of a global context, used as [`Global<M>`].\
+/// Named global contexts are declared with `#[global_context(name = Marker)]`, which also declares their marker type.
+/// ```rust,ignore
+/// #[global_context(name = Compute)]
+/// static COMPUTE : SimpleContext = SimpleContext::default();
+///
+/// let buffer = Buffer::new_in(Global::<Compute>::new(), &[1, 2, 3], MemAccess::default(), false)?;
+/// ```
+/// # Safety
+/// [`context`](GlobalMarker::context) must always return the same context.
+pub unsafe trait GlobalMarker: 'static {
+    type Context: ?Sized + Context + Sync;
+
+    /// Returns the global context of the marker.
+    fn context () -> &'static Self::Context;
+}
+
+/// Marker of the default global context, declared with `#[global_context]`.\
+/// If no default global context is declared, a [`SimpleContext::default`] is lazily created the first time it's used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DefaultGlobal {}
+
+unsafe impl GlobalMarker for DefaultGlobal {
+    type Context = dyn Context + Sync;
+
+    #[inline]
+    fn context () -> &'static Self::Context {
+        static FALLBACK : Lazy<SimpleContext> = Lazy::new(|| match SimpleContext::default() {
+            Ok(x) => x,
+            Err(e) => panic!("error creating the fallback global context: {e}")
+        });
+
+        static CONTEXT : Lazy<&'static (dyn Context + Sync)> = Lazy::new(|| match GLOBAL_CONTEXTS[..] {
+            [] => &*FALLBACK,
+            [f] => f(),
+            _ => panic!("only one default global context can be declared, but {} were found", GLOBAL_CONTEXTS.len())
+        });
+
+        *CONTEXT
+    }
 }
 
 #[doc = include_str!("../../docs/src/context/global.md")]
-#[derive(Copy, Clone, Default, Debug)]
-pub struct Global;
+pub struct Global<M: GlobalMarker = DefaultGlobal> {
+    phtm: PhantomData<fn() -> M>
+}
+
+/// The default global context.
+#[allow(non_upper_case_globals)]
+pub const Global : Global = Global::new();
+
+impl<M: GlobalMarker> Global<M> {
+    /// Returns a handle to the global context of `M`.
+    #[inline(always)]
+    pub const fn new () -> Self {
+        Self { phtm: PhantomData }
+    }
+}
 
 impl Global {
     /// Returns a reference to a static global allocator.
@@ -21,28 +78,56 @@ impl Global {
     }
 }
 
-impl Context for Global {
+impl<M: GlobalMarker> Context for Global<M> {
     #[inline(always)]
     fn next_queue (&self) -> &CommandQueue {
-        unsafe { __blaze__global__next_queue() } 
+        M::context().next_queue()
     }
 
     #[inline(always)]
     fn as_raw (&self) -> &RawContext {
-        unsafe { __blaze__global__as_raw() } 
+        M::context().as_raw()
     }
 
     #[inline(always)]
     fn queues (&self) -> &[CommandQueue] {
-        unsafe { __blaze__global__queues() }
+        M::context().queues()
+    }
+
+    #[inline(always)]
+    fn specializations (&self) -> Option<&Specializations> {
+        M::context().specializations()
     }
 }
 
-impl Deref for Global {
+impl<M: GlobalMarker> Deref for Global<M> {
     type Target = RawContext;
 
     #[inline(always)]
     fn deref(&self) -> &Self::Target {
         self.as_raw()
     }
-}
\ No newline at end of file
+}
+
+impl<M: GlobalMarker> Clone for Global<M> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M: GlobalMarker> Copy for Global<M> {}
+
+impl<M: GlobalMarker> Default for Global<M> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: GlobalMarker> Debug for Global<M> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Global<{}>", core::any::type_name::<M>())
+    }
+}
@@ -1,7 +1,7 @@
-use crate::prelude::Result;
+use crate::{core::Specializations, prelude::Result};
 use std::{rc::Rc, sync::Arc};
 
-flat_mod!(scope, raw, flags, global, single, queue);
+flat_mod!(scope, raw, flags, global, single, multi, select, queue, profiler);
 
 /// An object that can be used as a Blaze context, with a similar syntax to Rust allocators.\
 /// Blaze contexts are similar to OpenCL contexts, except they're also in charge of administrating and supplying
@@ -15,6 +15,13 @@ pub trait Context {
     /// Returns the next [`CommandQueue`], as per context implementation
     fn next_queue(&self) -> &CommandQueue;
 
+    /// Returns the cache of the context's [specialized generic programs](crate::core::RawProgram::from_source_specialized_in), if it has one.
+    /// Contexts without one build their specializations through the [program cache](crate::core::ProgramCache::global) every time.
+    #[inline(always)]
+    fn specializations(&self) -> Option<&Specializations> {
+        None
+    }
+
     /// Flushes all the [`CommandQueue`]s in the context.
     #[inline(always)]
     fn flush_all(&self) -> Result<()> {
@@ -49,6 +56,11 @@ impl<T: ?Sized + Context> Context for &T {
     fn next_queue(&self) -> &CommandQueue {
         T::next_queue(self)
     }
+
+    #[inline(always)]
+    fn specializations(&self) -> Option<&Specializations> {
+        T::specializations(self)
+    }
 }
 
 impl<T: ?Sized + Context> Context for Rc<T> {
@@ -66,6 +78,11 @@ impl<T: ?Sized + Context> Context for Rc<T> {
     fn next_queue(&self) -> &CommandQueue {
         T::next_queue(self)
     }
+
+    #[inline(always)]
+    fn specializations(&self) -> Option<&Specializations> {
+        T::specializations(self)
+    }
 }
 
 impl<T: ?Sized + Context> Context for Arc<T> {
@@ -83,4 +100,9 @@ impl<T: ?Sized + Context> Context for Arc<T> {
     fn next_queue(&self) -> &CommandQueue {
         T::next_queue(self)
     }
+
+    #[inline(always)]
+    fn specializations(&self) -> Option<&Specializations> {
+        T::specializations(self)
+    }
 }
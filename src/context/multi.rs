@@ -0,0 +1,233 @@
+use super::{CommandQueue, Context, ContextProperties, RawContext};
+use crate::core::*;
+use blaze_proc::docfg;
+use std::{
+    ops::Deref,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// A context with one or more command queues for each of its devices.
+/// The queue returned by [`next_queue`](Context::next_queue) is chosen by the context's [`QueuePolicy`].
+#[derive(Debug)]
+pub struct MultiContext<P = RoundRobin> {
+    ctx: RawContext,
+    queues: Box<[CommandQueue]>,
+    policy: P,
+    specializations: Specializations,
+}
+
+impl MultiContext {
+    /// Creates a new context spanning all the specified devices, with `queues_per_device` command queues for each of them.
+    /// The returned context uses a [round-robin](RoundRobin) policy, which can be changed with [`map_policy`](MultiContext::map_policy).
+    pub fn new(
+        devices: &[RawDevice],
+        queues_per_device: usize,
+        ctx_props: ContextProperties,
+        props: impl Into<QueueProperties>,
+    ) -> Result<Self> {
+        let ctx = RawContext::new(ctx_props, devices)?;
+        Self::with_queues(ctx, devices, queues_per_device, props.into())
+    }
+
+    /// Creates a new context spanning all the specified devices, with `queues_per_device` command queues for each of them.
+    /// The returned context uses a [round-robin](RoundRobin) policy, which can be changed with [`map_policy`](MultiContext::map_policy).
+    #[docfg(feature = "cl3")]
+    pub fn with_logger(
+        devices: &[RawDevice],
+        queues_per_device: usize,
+        ctx_props: ContextProperties,
+        props: impl Into<QueueProperties>,
+        loger: impl 'static + Fn(&std::ffi::CStr) + Send,
+    ) -> Result<Self> {
+        let ctx = RawContext::with_logger(ctx_props, devices, loger)?;
+        Self::with_queues(ctx, devices, queues_per_device, props.into())
+    }
+
+    fn with_queues(
+        ctx: RawContext,
+        devices: &[RawDevice],
+        queues_per_device: usize,
+        props: QueueProperties,
+    ) -> Result<Self> {
+        let mut queues = Vec::with_capacity(devices.len() * queues_per_device);
+        for device in devices {
+            for _ in 0..queues_per_device {
+                let queue = RawCommandQueue::new(&ctx, props, device)?;
+                queues.push(CommandQueue::new(queue));
+            }
+        }
+
+        Self::from_parts(ctx, queues.into_boxed_slice(), RoundRobin::default())
+    }
+}
+
+impl<P: QueuePolicy> MultiContext<P> {
+    /// Creates a new context from its raw parts. Returns an error if `queues` is empty.
+    #[inline]
+    pub fn from_parts(ctx: RawContext, queues: Box<[CommandQueue]>, policy: P) -> Result<Self> {
+        if queues.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidCommandQueue,
+                "a context must have at least one command queue",
+            ));
+        }
+
+        return Ok(Self {
+            ctx,
+            queues,
+            policy,
+            specializations: Specializations::new(),
+        });
+    }
+
+    /// Returns a reference to the context's policy.
+    #[inline(always)]
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+
+    /// Replaces the context's policy with the one returned by `f`.
+    /// ```rust,ignore
+    /// let ctx = MultiContext::new(&devices, 2, ContextProperties::default(), QueueProperties::default())?
+    ///     .map_policy(Weighted::new)?;
+    /// ```
+    #[inline]
+    pub fn map_policy<Q: QueuePolicy, F: FnOnce(&[CommandQueue]) -> Result<Q>>(
+        self,
+        f: F,
+    ) -> Result<MultiContext<Q>> {
+        let policy = f(&self.queues)?;
+        return Ok(MultiContext {
+            ctx: self.ctx,
+            queues: self.queues,
+            policy,
+            specializations: self.specializations,
+        });
+    }
+}
+
+impl<P: QueuePolicy> Context for MultiContext<P> {
+    #[inline(always)]
+    fn as_raw(&self) -> &RawContext {
+        &self.ctx
+    }
+
+    #[inline(always)]
+    fn queues(&self) -> &[CommandQueue] {
+        &self.queues
+    }
+
+    #[inline]
+    fn next_queue(&self) -> &CommandQueue {
+        let idx = self.policy.select(&self.queues);
+        &self.queues[idx]
+    }
+
+    #[inline(always)]
+    fn specializations(&self) -> Option<&Specializations> {
+        Some(&self.specializations)
+    }
+}
+
+impl<P> Deref for MultiContext<P> {
+    type Target = RawContext;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.ctx
+    }
+}
+
+/// Policy that decides which command queue of a [`MultiContext`] is returned by [`next_queue`](Context::next_queue).
+pub trait QueuePolicy {
+    /// Returns the index of the next queue. `queues` is guaranteed to not be empty.
+    fn select(&self, queues: &[CommandQueue]) -> usize;
+}
+
+impl<F: Fn(&[CommandQueue]) -> usize> QueuePolicy for F {
+    #[inline(always)]
+    fn select(&self, queues: &[CommandQueue]) -> usize {
+        self(queues)
+    }
+}
+
+/// Returns the command queues one after the other.
+#[derive(Debug, Default)]
+pub struct RoundRobin {
+    next: AtomicUsize,
+}
+
+impl QueuePolicy for RoundRobin {
+    #[inline]
+    fn select(&self, queues: &[CommandQueue]) -> usize {
+        self.next.fetch_add(1, Ordering::Relaxed) % queues.len()
+    }
+}
+
+/// Returns the command queue with the least [in-flight events](CommandQueue::size).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LeastLoaded;
+
+impl QueuePolicy for LeastLoaded {
+    #[inline]
+    fn select(&self, queues: &[CommandQueue]) -> usize {
+        queues
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, queue)| queue.size())
+            .map(|(i, _)| i)
+            .unwrap_or_default()
+    }
+}
+
+/// Weighted round-robin, where each command queue is returned proportionally to the [compute units](RawDevice::max_compute_units) of its device.
+#[derive(Debug)]
+pub struct Weighted {
+    /// Accumulated weights
+    bounds: Box<[usize]>,
+    next: AtomicUsize,
+}
+
+impl Weighted {
+    /// Creates a new policy with the weights of the specified queues.
+    pub fn new(queues: &[CommandQueue]) -> Result<Self> {
+        let mut weights = Vec::with_capacity(queues.len());
+        for queue in queues {
+            let units = queue.device()?.max_compute_units()?;
+            weights.push(usize::try_from(units.get()).unwrap());
+        }
+
+        Ok(Self::from_weights(weights))
+    }
+
+    /// Creates a new policy from the specified weights, one for each queue.
+    pub fn from_weights(weights: impl IntoIterator<Item = usize>) -> Self {
+        let mut total = 0usize;
+        let bounds = weights
+            .into_iter()
+            .map(|x| {
+                total = total.checked_add(x).expect("weight overflow");
+                total
+            })
+            .collect::<Box<[_]>>();
+
+        Self {
+            bounds,
+            next: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl QueuePolicy for Weighted {
+    #[inline]
+    fn select(&self, queues: &[CommandQueue]) -> usize {
+        let total = match self.bounds.last() {
+            Some(&x) if x > 0 => x,
+            _ => return 0,
+        };
+
+        let v = self.next.fetch_add(1, Ordering::Relaxed) % total;
+        let idx = self.bounds.partition_point(|&x| x <= v);
+        return idx.min(queues.len() - 1);
+    }
+}
@@ -0,0 +1,388 @@
+use super::Context;
+use crate::{
+    core::*,
+    event::{CommandType, EventStatus, ProfilingInfo, RawEvent},
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::Display,
+    io::Write,
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    time::Duration,
+};
+
+/// Number of live profilers. Kernel names are only queried while there is at least one.
+static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static KERNEL_LABEL: RefCell<Option<(usize, Arc<str>)>> = RefCell::new(None);
+}
+
+/// Context-level profiler, that records the timings of every event enqueued through the command queues it's attached to.
+///
+/// Timings are read with [`ProfilingInfo`], so the profiled queues must be created with profiling enabled.
+/// Events of queues without profiling are silently discarded.
+/// ```rust,ignore
+/// let profiler = Profiler::attach(Global::get());
+/// // ...
+/// profiler.save_chrome_trace("trace.json")?;
+/// println!("{}", profiler.summary());
+/// ```
+#[derive(Debug)]
+pub struct Profiler {
+    pending: Mutex<Vec<Pending>>,
+    records: Mutex<Vec<ProfileRecord>>,
+}
+
+#[derive(Debug)]
+struct Pending {
+    event: RawEvent,
+    queue: RawCommandQueue,
+    kernel: Option<Arc<str>>,
+}
+
+/// Timings of a single profiled command.
+#[derive(Debug, Clone)]
+pub struct ProfileRecord {
+    /// Command queue the command was enqueued in.
+    pub queue: RawCommandQueue,
+    /// Type of the command.
+    pub command: CommandType,
+    /// Name of the kernel, if the command is a kernel launch.
+    pub kernel: Option<Arc<str>>,
+    /// Device timings of the command, in nanoseconds.
+    pub info: ProfilingInfo<u64>,
+}
+
+impl ProfileRecord {
+    /// Returns the kernel's name, or the command's type if it isn't a kernel launch.
+    #[inline]
+    pub fn name(&self) -> String {
+        match self.kernel {
+            Some(ref name) => name.to_string(),
+            None => format!("{:?}", self.command),
+        }
+    }
+
+    /// Time the command spent executing on the device.
+    #[inline(always)]
+    pub fn duration(&self) -> Duration {
+        self.info.duration()
+    }
+}
+
+impl Profiler {
+    /// Creates a new profiler, not attached to any command queue.
+    #[inline]
+    pub fn new() -> Self {
+        ACTIVE.fetch_add(1, Ordering::AcqRel);
+        Self {
+            pending: Mutex::new(Vec::new()),
+            records: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Creates a new profiler and attaches it to every command queue of the context.
+    #[inline]
+    pub fn attach<C: ?Sized + Context>(ctx: &C) -> Arc<Self> {
+        let profiler = Arc::new(Self::new());
+        for queue in ctx.queues() {
+            queue.set_profiler(Some(profiler.clone()));
+        }
+        profiler
+    }
+
+    /// Detaches any profiler from the command queues of the context.
+    #[inline]
+    pub fn detach<C: ?Sized + Context>(ctx: &C) {
+        for queue in ctx.queues() {
+            queue.set_profiler(None);
+        }
+    }
+
+    /// Registers an event enqueued in `queue`.
+    pub fn record(&self, queue: &RawCommandQueue, event: &RawEvent) {
+        let id = event.id() as usize;
+        let kernel = KERNEL_LABEL.with(|label| match label.borrow_mut().take() {
+            Some((evt, name)) if evt == id => Some(name),
+            _ => None,
+        });
+
+        lock(&self.pending).push(Pending {
+            event: event.clone(),
+            queue: queue.clone(),
+            kernel,
+        })
+    }
+
+    /// Returns the records of all the completed events. Events that haven't completed yet are kept until the next call.
+    pub fn records(&self) -> Vec<ProfileRecord> {
+        let mut records = lock(&self.records);
+        lock(&self.pending).retain(|pending| match pending.event.status() {
+            Ok(EventStatus::Complete) => {
+                if let (Ok(command), Ok(info)) =
+                    (pending.event.ty(), pending.event.profiling_nanos())
+                {
+                    records.push(ProfileRecord {
+                        queue: pending.queue.clone(),
+                        command,
+                        kernel: pending.kernel.clone(),
+                        info,
+                    });
+                }
+                false
+            }
+            Ok(_) => true,
+            Err(_) => false,
+        });
+
+        records.clone()
+    }
+
+    /// Removes all the records, including the ones of pending events.
+    #[inline]
+    pub fn clear(&self) {
+        lock(&self.pending).clear();
+        lock(&self.records).clear();
+    }
+
+    /// Returns a per-kernel summary of the completed kernel launches, sorted by total time.
+    pub fn summary(&self) -> ProfileSummary {
+        let mut durations = HashMap::<Arc<str>, Vec<Duration>>::new();
+        for record in self.records() {
+            if let Some(kernel) = record.kernel.clone() {
+                durations.entry(kernel).or_default().push(record.duration());
+            }
+        }
+
+        let mut kernels = durations
+            .into_iter()
+            .map(|(name, mut durations)| {
+                durations.sort_unstable();
+                let count = durations.len();
+                let total = durations.iter().sum::<Duration>();
+                let p95 = durations[((count * 95 + 99) / 100).max(1) - 1];
+
+                KernelSummary {
+                    name,
+                    count,
+                    total,
+                    mean: total / u32::try_from(count).unwrap_or(u32::MAX),
+                    p95,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        kernels.sort_by(|a, b| b.total.cmp(&a.total));
+        ProfileSummary { kernels }
+    }
+
+    /// Writes the completed records in the [Chrome trace event format](https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU),
+    /// which can be opened with [Perfetto](https://ui.perfetto.dev) or `chrome://tracing`.
+    /// Each device is shown as a process, and each of its command queues as a thread.
+    pub fn write_chrome_trace<W: Write>(&self, mut w: W) -> std::io::Result<()> {
+        let records = self.records();
+        let origin = records
+            .iter()
+            .map(|x| x.info.queued)
+            .min()
+            .unwrap_or_default();
+
+        let mut devices = Vec::<RawDevice>::new();
+        let mut queues = Vec::<(usize, usize)>::new();
+
+        write!(w, "{{\"displayTimeUnit\":\"ns\",\"traceEvents\":[")?;
+        let mut first = true;
+        let mut sep = |w: &mut W| -> std::io::Result<()> {
+            if !core::mem::take(&mut first) {
+                w.write_all(b",")?;
+            }
+            Ok(())
+        };
+
+        for record in records.iter() {
+            let queue_id = record.queue.id() as usize;
+            let (pid, tid) = match queues.iter().position(|(id, _)| *id == queue_id) {
+                Some(tid) => (queues[tid].1, tid),
+                None => {
+                    let device = record.queue.device().ok();
+                    let pid = match device
+                        .as_ref()
+                        .and_then(|x| devices.iter().position(|y| y == x))
+                    {
+                        Some(pid) => pid,
+                        None => {
+                            let name = device
+                                .as_ref()
+                                .and_then(|x| x.name().ok())
+                                .unwrap_or_default();
+                            sep(&mut w)?;
+                            write!(w, "{{\"name\":\"process_name\",\"ph\":\"M\",\"pid\":{},\"args\":{{\"name\":\"{}\"}}}}", devices.len(), Escape(&name))?;
+                            let pid = devices.len();
+                            if let Some(device) = device {
+                                devices.push(device);
+                            }
+                            pid
+                        }
+                    };
+
+                    let tid = queues.len();
+                    queues.push((queue_id, pid));
+                    sep(&mut w)?;
+                    write!(w, "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":{pid},\"tid\":{tid},\"args\":{{\"name\":\"Queue {tid}\"}}}}")?;
+                    (pid, tid)
+                }
+            };
+
+            let cat = match record.command {
+                CommandType::NdRangeKernel | CommandType::Task | CommandType::NativeKernel => {
+                    "kernel"
+                }
+                CommandType::Marker | CommandType::Barrier | CommandType::User => "sync",
+                _ => "transfer",
+            };
+
+            let info = &record.info;
+            sep(&mut w)?;
+            write!(
+                w,
+                "{{\"name\":\"{}\",\"cat\":\"{cat}\",\"ph\":\"X\",\"pid\":{pid},\"tid\":{tid},\"ts\":{},\"dur\":{},\"args\":{{\"command\":\"{:?}\",\"queued\":{},\"submit\":{}}}}}",
+                Escape(&record.name()),
+                Micros(info.start.saturating_sub(origin)),
+                Micros(info.end.saturating_sub(info.start)),
+                record.command,
+                Micros(info.queued.saturating_sub(origin)),
+                Micros(info.submit.saturating_sub(origin)),
+            )?;
+        }
+
+        write!(w, "]}}")?;
+        w.flush()
+    }
+
+    /// Writes the completed records into the specified file, in the Chrome trace event format.
+    /// See [`write_chrome_trace`](Profiler::write_chrome_trace).
+    #[inline]
+    pub fn save_chrome_trace(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        self.write_chrome_trace(std::io::BufWriter::new(file))
+    }
+}
+
+impl Default for Profiler {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Profiler {
+    #[inline(always)]
+    fn drop(&mut self) {
+        ACTIVE.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Per-kernel statistics of a [`Profiler`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KernelSummary {
+    pub name: Arc<str>,
+    pub count: usize,
+    pub total: Duration,
+    pub mean: Duration,
+    /// 95th percentile of the kernel's durations.
+    pub p95: Duration,
+}
+
+/// Summary of the kernel launches recorded by a [`Profiler`]. When displayed, it's formatted as a table.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileSummary {
+    pub kernels: Vec<KernelSummary>,
+}
+
+impl Display for ProfileSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self
+            .kernels
+            .iter()
+            .map(|x| x.name.len())
+            .max()
+            .unwrap_or_default()
+            .max(6);
+
+        writeln!(
+            f,
+            "{:<width$} {:>8} {:>14} {:>14} {:>14}",
+            "kernel", "count", "total", "mean", "p95"
+        )?;
+        for kernel in self.kernels.iter() {
+            writeln!(
+                f,
+                "{:<width$} {:>8} {:>14} {:>14} {:>14}",
+                kernel.name,
+                kernel.count,
+                format!("{:?}", kernel.total),
+                format!("{:?}", kernel.mean),
+                format!("{:?}", kernel.p95)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Labels a kernel launch, so that the profiler can know the name of its kernel.
+#[inline]
+pub(crate) fn label_kernel_event(event: &RawEvent, kernel: &RawKernel) {
+    if ACTIVE.load(Ordering::Acquire) == 0 {
+        return;
+    }
+
+    if let Ok(name) = kernel.name() {
+        KERNEL_LABEL
+            .with(|label| *label.borrow_mut() = Some((event.id() as usize, Arc::from(name))));
+    }
+}
+
+#[inline(always)]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(x) => x,
+        Err(e) => e.into_inner(),
+    }
+}
+
+/// Formats nanoseconds as microseconds, the time unit of the trace event format.
+struct Micros(u64);
+
+impl Display for Micros {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{:03}", self.0 / 1000, self.0 % 1000)
+    }
+}
+
+/// Escapes a string for JSON.
+struct Escape<'a>(&'a str);
+
+impl Display for Escape<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use std::fmt::Write;
+
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+                c => f.write_char(c)?,
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,11 +1,13 @@
-use std::{sync::{atomic::{AtomicUsize, Ordering}, Arc}, ops::{Deref, DerefMut}, marker::PhantomData};
+use std::{sync::{atomic::{AtomicUsize, Ordering}, Arc, RwLock}, ops::{Deref, DerefMut}, marker::PhantomData};
 use crate::{prelude::{RawCommandQueue, Result, Event, RawEvent}, event::consumer::*};
+use super::Profiler;
 
 /// A command queue with extra capabilities to a raw OpenCL one.
 #[derive(Debug, Clone)]
 pub struct CommandQueue {
     inner: RawCommandQueue,
-    pub(super) size: Arc<AtomicUsize>
+    pub(super) size: Arc<AtomicUsize>,
+    profiler: Arc<RwLock<Option<Arc<Profiler>>>>
 }
 
 impl CommandQueue {
@@ -14,7 +16,41 @@ impl CommandQueue {
     pub fn new (inner: RawCommandQueue) -> Self {
         Self {
             inner,
-            size: Arc::new(AtomicUsize::default())
+            size: Arc::new(AtomicUsize::default()),
+            profiler: Arc::new(RwLock::new(None))
+        }
+    }
+
+    /// Returns the [`Profiler`] attached to the queue, if any.
+    #[inline]
+    pub fn profiler (&self) -> Option<Arc<Profiler>> {
+        match self.profiler.read() {
+            Ok(x) => x.clone(),
+            Err(e) => e.into_inner().clone()
+        }
+    }
+
+    /// Attaches a [`Profiler`] to the queue (and all of its clones), returning the previous one.
+    /// Passing [`None`] detaches the current profiler.
+    #[inline]
+    pub fn set_profiler (&self, profiler: Option<Arc<Profiler>>) -> Option<Arc<Profiler>> {
+        let mut current = match self.profiler.write() {
+            Ok(x) => x,
+            Err(e) => e.into_inner()
+        };
+        core::mem::replace(&mut current, profiler)
+    }
+
+    /// Registers a newly enqueued event in the queue's profiler, if any.
+    #[inline]
+    pub(super) fn profile (&self, event: &RawEvent) {
+        let profiler = match self.profiler.read() {
+            Ok(x) => x,
+            Err(e) => e.into_inner()
+        };
+
+        if let Some(ref profiler) = *profiler {
+            profiler.record(&self.inner, event)
         }
     }
 
@@ -30,6 +66,7 @@ impl CommandQueue {
     #[inline]
     pub unsafe fn enqueue_unchecked<'a, 'b, 'r: 'b, E: 'b + FnOnce(&'r RawCommandQueue) -> Result<RawEvent>, C: 'a + Consumer> (&'r self, supplier: E, consumer: C) -> Result<Event<C>> {
         let inner = supplier(&self.inner)?;
+        self.profile(&inner);
         let evt = Event::new(inner, consumer);
 
         if self.size.fetch_add(1, Ordering::AcqRel) == usize::MAX {
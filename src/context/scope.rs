@@ -83,6 +83,7 @@ impl<'scope, 'env: 'scope, C: 'env + Context> Scope<'scope, 'env, C> {
     ) -> Result<Event<F>> {
         let queue = self.ctx.next_queue();
         let inner = supplier(&queue)?;
+        queue.profile(&inner);
         let evt = Event::new(inner, consumer);
 
         if self.data.items.fetch_add(1, Ordering::AcqRel) == usize::MAX {
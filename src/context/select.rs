@@ -0,0 +1,468 @@
+use super::{ContextProperties, MultiContext, SimpleContext};
+use crate::core::{
+    device::{DeviceType, Version},
+    *,
+};
+use blaze_proc::docfg;
+use std::fmt::{Debug, Display};
+
+/// Builder used to choose the devices of a context by a set of criteria.
+///
+/// Every device in the system is a candidate, and it's accepted only if it fulfills all the specified requirements.
+/// Accepted devices are ranked by their [score](DeviceSelector::score), keeping the system order for ties.
+///
+/// If the `BLAZE_DEVICE` environment variable is set (with the format `platform:device`, e.g. `BLAZE_DEVICE=1:0`),
+/// the device it points to is selected instead, regardless of the requirements.
+/// ```rust,ignore
+/// let ctx = DeviceSelector::new()
+///     .ty(DeviceType::GPU)
+///     .f64()
+///     .min_global_mem_size(4 << 30)
+///     .build_simple(ContextProperties::default(), QueueProperties::default())?;
+/// ```
+pub struct DeviceSelector {
+    ty: Option<DeviceType>,
+    vendor: Option<Pattern>,
+    name: Option<Pattern>,
+    extensions: Vec<String>,
+    min_global_mem_size: Option<u64>,
+    min_version: Option<Version>,
+    score: Option<Box<dyn Fn(&RawDevice) -> Result<i64>>>,
+    env: bool,
+}
+
+impl DeviceSelector {
+    /// Environment variable used to override the selection.
+    pub const ENV_VAR: &'static str = "BLAZE_DEVICE";
+
+    /// Creates a new selector that accepts every device.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self {
+            ty: None,
+            vendor: None,
+            name: None,
+            extensions: Vec::new(),
+            min_global_mem_size: None,
+            min_version: None,
+            score: None,
+            env: true,
+        }
+    }
+
+    /// Only accept devices of (any of) the specified type(s).
+    #[inline(always)]
+    pub fn ty(mut self, ty: DeviceType) -> Self {
+        self.ty = Some(ty);
+        self
+    }
+
+    /// Only accept devices whose vendor contains `vendor` (case insensitive).
+    #[inline(always)]
+    pub fn vendor(mut self, vendor: impl AsRef<str>) -> Self {
+        self.vendor = Some(Pattern::Contains(vendor.as_ref().to_lowercase()));
+        self
+    }
+
+    /// Only accept devices whose vendor matches the regular expression.
+    #[docfg(feature = "regex")]
+    #[inline(always)]
+    pub fn vendor_regex(mut self, vendor: regex::Regex) -> Self {
+        self.vendor = Some(Pattern::Regex(vendor));
+        self
+    }
+
+    /// Only accept devices whose name contains `name` (case insensitive).
+    #[inline(always)]
+    pub fn name(mut self, name: impl AsRef<str>) -> Self {
+        self.name = Some(Pattern::Contains(name.as_ref().to_lowercase()));
+        self
+    }
+
+    /// Only accept devices whose name matches the regular expression.
+    #[docfg(feature = "regex")]
+    #[inline(always)]
+    pub fn name_regex(mut self, name: regex::Regex) -> Self {
+        self.name = Some(Pattern::Regex(name));
+        self
+    }
+
+    /// Only accept devices that support the specified extension.
+    #[inline(always)]
+    pub fn extension(mut self, extension: impl Into<String>) -> Self {
+        self.extensions.push(extension.into());
+        self
+    }
+
+    /// Only accept devices with support for double precision floating-point numbers (`cl_khr_fp64`).
+    #[inline(always)]
+    pub fn f64(self) -> Self {
+        self.extension("cl_khr_fp64")
+    }
+
+    /// Only accept devices with support for half precision floating-point numbers (`cl_khr_fp16`).
+    #[inline(always)]
+    pub fn f16(self) -> Self {
+        self.extension("cl_khr_fp16")
+    }
+
+    /// Only accept devices with at least `size` bytes of global memory.
+    #[inline(always)]
+    pub fn min_global_mem_size(mut self, size: u64) -> Self {
+        self.min_global_mem_size = Some(size);
+        self
+    }
+
+    /// Only accept devices with an OpenCL version greater or equal to `version`.
+    #[inline(always)]
+    pub fn min_version(mut self, version: Version) -> Self {
+        self.min_version = Some(version);
+        self
+    }
+
+    /// Ranks the accepted devices by the specified score, in descending order.
+    #[inline(always)]
+    pub fn score(mut self, f: impl 'static + Fn(&RawDevice) -> Result<i64>) -> Self {
+        self.score = Some(Box::new(f));
+        self
+    }
+
+    /// Ignores the `BLAZE_DEVICE` environment variable.
+    #[inline(always)]
+    pub fn ignore_env(mut self) -> Self {
+        self.env = false;
+        self
+    }
+
+    /// Checks every device in the system, returning them alongside the reason(s) they were rejected, if any.
+    pub fn candidates(&self) -> Result<Vec<Candidate>> {
+        let env = match self.env {
+            true => env_override()?,
+            false => None,
+        };
+
+        let mut result = Vec::with_capacity(RawDevice::all().len());
+        let mut platform_devices = Vec::<(RawPlatform, usize)>::new();
+
+        for device in RawDevice::all() {
+            let platform = device.platform()?;
+            let platform_idx = RawPlatform::all()
+                .iter()
+                .position(|x| x == &platform)
+                .unwrap_or_default();
+
+            let device_idx = match platform_devices.iter_mut().find(|(x, _)| x == &platform) {
+                Some((_, cnt)) => {
+                    *cnt += 1;
+                    *cnt - 1
+                }
+                None => {
+                    platform_devices.push((platform, 1));
+                    0
+                }
+            };
+
+            let status = match env {
+                Some(env) if env == (platform_idx, device_idx) => Ok(0),
+                Some(_) => Err(vec![Rejection::EnvOverride]),
+                None => self.check(device),
+            };
+
+            result.push(Candidate {
+                device: device.clone(),
+                platform: platform_idx,
+                index: device_idx,
+                status,
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// Returns all the accepted devices, ranked by their score. The returned devices all belong to the same platform as the best one.
+    pub fn select_all(&self) -> Result<Vec<RawDevice>> {
+        let candidates = self.candidates()?;
+        if candidates.iter().all(|x| x.status.is_err()) {
+            return Err(Error::new(ErrorKind::DeviceNotFound, NoDevice(candidates)));
+        }
+
+        let mut accepted = candidates
+            .iter()
+            .filter_map(|x| x.status.as_ref().ok().map(|score| (x, *score)))
+            .collect::<Vec<_>>();
+
+        accepted.sort_by(|(_, x), (_, y)| y.cmp(x));
+        let platform = accepted[0].0.platform;
+
+        return Ok(accepted
+            .into_iter()
+            .filter(|(x, _)| x.platform == platform)
+            .map(|(x, _)| x.device.clone())
+            .collect());
+    }
+
+    /// Returns the best accepted device.
+    #[inline]
+    pub fn select(&self) -> Result<RawDevice> {
+        let mut devices = self.select_all()?;
+        Ok(devices.swap_remove(0))
+    }
+
+    /// Creates a [`SimpleContext`] with the best accepted device.
+    #[inline]
+    pub fn build_simple(
+        &self,
+        ctx_props: ContextProperties,
+        props: impl Into<QueueProperties>,
+    ) -> Result<SimpleContext> {
+        let device = self.select()?;
+        SimpleContext::new(&device, ctx_props, props)
+    }
+
+    /// Creates a [`MultiContext`] with all the accepted devices (of the best device's platform), with `queues_per_device` command queues for each of them.
+    #[inline]
+    pub fn build_multi(
+        &self,
+        queues_per_device: usize,
+        ctx_props: ContextProperties,
+        props: impl Into<QueueProperties>,
+    ) -> Result<MultiContext> {
+        let devices = self.select_all()?;
+        MultiContext::new(&devices, queues_per_device, ctx_props, props)
+    }
+
+    fn check(&self, device: &RawDevice) -> core::result::Result<i64, Vec<Rejection>> {
+        let mut reasons = Vec::new();
+        macro_rules! tri_reject {
+            ($e:expr) => {
+                match $e {
+                    Ok(x) => x,
+                    Err(e) => return Err(vec![Rejection::Error(e)]),
+                }
+            };
+        }
+
+        if let Some(ty) = self.ty {
+            let found = tri_reject!(device.ty());
+            if !found.intersects(ty) {
+                reasons.push(Rejection::Type {
+                    expected: ty,
+                    found,
+                });
+            }
+        }
+
+        if let Some(ref vendor) = self.vendor {
+            let found = tri_reject!(device.vendor());
+            if !vendor.matches(&found) {
+                reasons.push(Rejection::Vendor(found));
+            }
+        }
+
+        if let Some(ref name) = self.name {
+            let found = tri_reject!(device.name());
+            if !name.matches(&found) {
+                reasons.push(Rejection::Name(found));
+            }
+        }
+
+        if !self.extensions.is_empty() {
+            let found = tri_reject!(device.extensions());
+            for ext in self.extensions.iter() {
+                if !found.contains(ext) {
+                    reasons.push(Rejection::MissingExtension(ext.clone()));
+                }
+            }
+        }
+
+        if let Some(min) = self.min_global_mem_size {
+            let found = tri_reject!(device.global_mem_size());
+            if found < min {
+                reasons.push(Rejection::GlobalMemSize { min, found });
+            }
+        }
+
+        if let Some(min) = self.min_version {
+            let found = tri_reject!(device.version());
+            if found < min {
+                reasons.push(Rejection::Version { min, found });
+            }
+        }
+
+        if !reasons.is_empty() {
+            return Err(reasons);
+        }
+
+        return match self.score {
+            Some(ref f) => Ok(tri_reject!(f(device))),
+            None => Ok(0),
+        };
+    }
+}
+
+impl Default for DeviceSelector {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for DeviceSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeviceSelector")
+            .field("ty", &self.ty)
+            .field("vendor", &self.vendor)
+            .field("name", &self.name)
+            .field("extensions", &self.extensions)
+            .field("min_global_mem_size", &self.min_global_mem_size)
+            .field("min_version", &self.min_version)
+            .field("env", &self.env)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A device checked by a [`DeviceSelector`].
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    /// The checked device
+    pub device: RawDevice,
+    /// Index of the device's platform
+    pub platform: usize,
+    /// Index of the device inside its platform
+    pub index: usize,
+    /// Score of the device if it was accepted, or the reasons it was rejected
+    pub status: core::result::Result<i64, Vec<Rejection>>,
+}
+
+/// Reason a device was rejected by a [`DeviceSelector`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum Rejection {
+    Type {
+        expected: DeviceType,
+        found: DeviceType,
+    },
+    Vendor(String),
+    Name(String),
+    MissingExtension(String),
+    GlobalMemSize {
+        min: u64,
+        found: u64,
+    },
+    Version {
+        min: Version,
+        found: Version,
+    },
+    /// Another device was selected through the `BLAZE_DEVICE` environment variable
+    EnvOverride,
+    /// The device couldn't be queried
+    Error(Error),
+}
+
+impl Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Type { expected, found } => {
+                write!(f, "expected type {expected:?}, found {found:?}")
+            }
+            Self::Vendor(found) => write!(f, "vendor '{found}' doesn't match"),
+            Self::Name(found) => write!(f, "name '{found}' doesn't match"),
+            Self::MissingExtension(ext) => write!(f, "missing extension '{ext}'"),
+            Self::GlobalMemSize { min, found } => {
+                write!(
+                    f,
+                    "global memory size of {found} bytes, expected at least {min}"
+                )
+            }
+            Self::Version { min, found } => {
+                write!(f, "OpenCL version {found}, expected at least {min}")
+            }
+            Self::EnvOverride => write!(f, "not the device selected by `BLAZE_DEVICE`"),
+            Self::Error(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+enum Pattern {
+    Contains(String),
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+impl Pattern {
+    #[inline]
+    fn matches(&self, v: &str) -> bool {
+        match self {
+            Self::Contains(x) => v.to_lowercase().contains(x),
+            #[cfg(feature = "regex")]
+            Self::Regex(x) => x.is_match(v),
+        }
+    }
+}
+
+impl Debug for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Contains(x) => Debug::fmt(x, f),
+            #[cfg(feature = "regex")]
+            Self::Regex(x) => Debug::fmt(x, f),
+        }
+    }
+}
+
+struct NoDevice(Vec<Candidate>);
+
+impl Display for NoDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "no devices found");
+        }
+
+        write!(f, "no device fulfills the requirements")?;
+        for candidate in self.0.iter() {
+            let name = candidate
+                .device
+                .name()
+                .unwrap_or_else(|_| String::from("<unknown>"));
+            write!(
+                f,
+                "\n  {}:{} '{name}' rejected: ",
+                candidate.platform, candidate.index
+            )?;
+
+            if let Err(ref reasons) = candidate.status {
+                for (i, reason) in reasons.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{reason}")?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn env_override() -> Result<Option<(usize, usize)>> {
+    let v = match std::env::var(DeviceSelector::ENV_VAR) {
+        Ok(x) if !x.trim().is_empty() => x,
+        _ => return Ok(None),
+    };
+
+    let parse = || -> Option<(usize, usize)> {
+        let (platform, device) = v.trim().split_once(':')?;
+        Some((platform.trim().parse().ok()?, device.trim().parse().ok()?))
+    };
+
+    match parse() {
+        Some(x) => Ok(Some(x)),
+        None => Err(Error::new(
+            ErrorKind::InvalidValue,
+            format!(
+                "invalid value '{v}' for `{}`, expected `platform:device`",
+                DeviceSelector::ENV_VAR
+            ),
+        )),
+    }
+}
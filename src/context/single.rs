@@ -1,32 +1,35 @@
-use std::{ops::Deref};
+use std::{ops::Deref, sync::Arc};
 use blaze_proc::docfg;
 use crate::{core::*};
-use super::{Context, RawContext, ContextProperties, CommandQueue};
+use super::{Context, RawContext, ContextProperties, CommandQueue, DeviceSelector};
 
 #[doc = include_str!("../../docs/src/context/simple.md")]
 #[derive(Clone)]
 pub struct SimpleContext {
     ctx: RawContext,
-    queue: CommandQueue
+    queue: CommandQueue,
+    specializations: Arc<Specializations>
 }
 
 impl SimpleContext {
     pub fn new (device: &RawDevice, ctx_props: ContextProperties, props: impl Into<QueueProperties>) -> Result<Self> {
         let ctx = RawContext::new(ctx_props, core::slice::from_ref(device))?;
         let queue = RawCommandQueue::new(&ctx, props.into(), device).map(CommandQueue::new)?;
-        Ok(Self { ctx, queue })
+        Ok(Self { ctx, queue, specializations: Arc::default() })
     }
 
     #[docfg(feature = "cl3")]
     pub fn with_logger (device: &RawDevice, ctx_props: ContextProperties, props: impl Into<QueueProperties>, loger: impl 'static + Fn(&std::ffi::CStr) + Send) -> Result<Self> {
         let ctx = RawContext::with_logger(ctx_props, core::slice::from_ref(device), loger)?;
         let queue = RawCommandQueue::new(&ctx, props.into(), device).map(CommandQueue::new)?;
-        Ok(Self { ctx, queue })
+        Ok(Self { ctx, queue, specializations: Arc::default() })
     }
 
+    /// Creates a new context with the first device of the system, or with the one specified by the `BLAZE_DEVICE` environment variable.
+    /// For more control over the selected device, see [`DeviceSelector`].
     #[inline(always)]
     pub fn default() -> Result<Self> {
-        let device = RawDevice::first().ok_or(ErrorKind::InvalidDevice)?;
+        let device = &DeviceSelector::new().select()?;
 
         cfg_if::cfg_if! {
             if #[cfg(all(debug_assertions, feature = "cl3"))] {
@@ -53,6 +56,11 @@ impl Context for SimpleContext {
     fn next_queue (&self) -> &CommandQueue {
         &self.queue
     }
+
+    #[inline(always)]
+    fn specializations (&self) -> Option<&Specializations> {
+        Some(&self.specializations)
+    }
 }
 
 impl Deref for SimpleContext {
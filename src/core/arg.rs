@@ -0,0 +1,118 @@
+/// Types that can be passed to kernels (by value or through a pointer) with the same layout on the host and on the device.
+///
+/// Structs can implement it with `#[derive(KernelArg)]`, which requires them to be `#[repr(C)]` and every field to implement [`KernelArg`].
+/// The derive generates the equivalent OpenCL C `typedef struct`, which must be included in the program's source before the struct is used.
+/// The definition also asserts that the size and alignment of the struct on the device match the ones on the host, so a mismatch fails the program's build.
+/// ```rust,ignore
+/// #[derive(Debug, Clone, Copy, KernelArg)]
+/// #[repr(C)]
+/// struct Particle {
+///     position: [f32; 2],
+///     mass: f32,
+/// }
+///
+/// let source = format!("{}{}", Particle::CL_DEFINITION, KERNEL);
+/// ```
+pub unsafe trait KernelArg: Copy {
+    /// Name of the type in OpenCL C.
+    const CL_TYPE: &'static str;
+    /// OpenCL C definition of the type. Empty for builtin types.
+    const CL_DEFINITION: &'static str = "";
+}
+
+macro_rules! impl_scalar {
+    ($($ty:ty => $cl:literal),+) => {
+        $(
+            unsafe impl KernelArg for $ty {
+                const CL_TYPE: &'static str = $cl;
+            }
+        )+
+    };
+}
+
+impl_scalar! {
+    i8 => "char",
+    u8 => "uchar",
+    i16 => "short",
+    u16 => "ushort",
+    i32 => "int",
+    u32 => "uint",
+    i64 => "long",
+    u64 => "ulong",
+    f32 => "float",
+    f64 => "double"
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "half")))]
+#[cfg(feature = "half")]
+unsafe impl KernelArg for ::half::f16 {
+    const CL_TYPE: &'static str = "half";
+}
+
+/// Part of an OpenCL C definition generated by `#[derive(KernelArg)]`.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub enum ClPart {
+    Str(&'static str),
+    Int(usize),
+}
+
+/// Length in bytes of the concatenated parts.
+#[doc(hidden)]
+pub const fn cl_len(parts: &[ClPart]) -> usize {
+    let mut len = 0;
+    let mut i = 0;
+    while i < parts.len() {
+        len += match parts[i] {
+            ClPart::Str(s) => s.len(),
+            ClPart::Int(mut x) => {
+                let mut digits = 1;
+                while x >= 10 {
+                    x /= 10;
+                    digits += 1;
+                }
+                digits
+            }
+        };
+        i += 1;
+    }
+
+    len
+}
+
+/// Concatenates the parts at compile time. `N` must be the result of [`cl_len`].
+#[doc(hidden)]
+pub const fn cl_concat<const N: usize>(parts: &[ClPart]) -> [u8; N] {
+    let mut result = [0; N];
+    let mut len = 0;
+    let mut i = 0;
+
+    while i < parts.len() {
+        match parts[i] {
+            ClPart::Str(s) => {
+                let s = s.as_bytes();
+                let mut j = 0;
+                while j < s.len() {
+                    result[len] = s[j];
+                    len += 1;
+                    j += 1;
+                }
+            }
+
+            ClPart::Int(x) => {
+                let digits = cl_len(&[ClPart::Int(x)]);
+                let mut x = x;
+                let mut j = digits;
+                while j > 0 {
+                    j -= 1;
+                    result[len + j] = b'0' + (x % 10) as u8;
+                    x /= 10;
+                }
+                len += digits;
+            }
+        }
+        i += 1;
+    }
+
+    result
+}
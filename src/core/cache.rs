@@ -0,0 +1,326 @@
+use super::*;
+use crate::context::{Context, Global};
+use std::{
+    fs::File,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        RwLock,
+    },
+};
+
+const MAGIC: &[u8; 4] = b"BLZC";
+const FORMAT_VERSION: u32 = 3;
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+lazy_static! {
+    static ref GLOBAL_CACHE: RwLock<Option<ProgramCache>> = RwLock::new(ProgramCache::from_env());
+}
+
+/// On-disk cache of program binaries.
+///
+/// Entries are keyed by the program's source, its build options and the name, driver version and platform of every device in the context.
+/// The full key and the build options are stored alongside the binaries, so entries whose key hashes collide are never mixed up.
+/// On a hit, the program is loaded with [`from_binaries_with_options_in`](RawProgram::from_binaries_with_options_in), using the stored build options. On a miss, it's built from source and its [`binaries`](RawProgram::binaries) are stored for the next time.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProgramCache {
+    dir: PathBuf,
+}
+
+impl ProgramCache {
+    /// Environment variable used to enable the global cache.
+    pub const ENV_VAR: &'static str = "BLAZE_CACHE_DIR";
+
+    /// Creates a new cache that stores its entries inside `dir`. The directory is created when the first entry is stored.
+    #[inline(always)]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Creates a new cache in the directory specified by the `BLAZE_CACHE_DIR` environment variable, if any.
+    #[inline]
+    pub fn from_env() -> Option<Self> {
+        match std::env::var_os(Self::ENV_VAR) {
+            Some(dir) if !dir.is_empty() => Some(Self::new(dir)),
+            _ => None,
+        }
+    }
+
+    /// Returns the cache's directory.
+    #[inline(always)]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the global cache, used by [`from_source_cached_in`](RawProgram::from_source_cached_in) and by the programs generated with `#[blaze]`.
+    /// By default, the global cache is only enabled if the `BLAZE_CACHE_DIR` environment variable is set.
+    #[inline]
+    pub fn global() -> Option<ProgramCache> {
+        match GLOBAL_CACHE.read() {
+            Ok(x) => x.clone(),
+            Err(e) => e.into_inner().clone(),
+        }
+    }
+
+    /// Sets the global cache, returning the previous one. Passing [`None`] disables the global cache.
+    #[inline]
+    pub fn set_global(cache: Option<ProgramCache>) -> Option<ProgramCache> {
+        let mut global = match GLOBAL_CACHE.write() {
+            Ok(x) => x,
+            Err(e) => e.into_inner(),
+        };
+
+        core::mem::replace(&mut global, cache)
+    }
+
+    /// Loads the program from the cache, or builds it from source and stores its binaries if it wasn't cached.
+    /// Failing to read or write a cache entry is not an error, the program is built from source instead.
+    pub fn load_or_build_in<C: Context>(
+        &self,
+        ctx: &C,
+        source: impl AsRef<str>,
+        options: Option<&str>,
+    ) -> Result<(RawProgram, Box<[RawKernel]>)> {
+        let source = source.as_ref();
+        let key = full_key_in(ctx, source, options)?;
+        let path = self.entry_path(hash_key(&key));
+
+        if let Some((options, binaries)) = read_entry(&path, &key) {
+            let binaries = binaries.iter().map(Vec::as_slice).collect::<Vec<_>>();
+            if let Ok(result) =
+                RawProgram::from_binaries_with_options_in(ctx, &binaries, options.as_deref())
+            {
+                return Ok(result);
+            }
+        }
+
+        let (program, kernels) = RawProgram::from_source_in(ctx, source, options)?;
+        if program.devices()? == ctx.as_raw().devices()? {
+            if let Some(binaries) = program.binaries()?.into_iter().collect::<Option<Vec<_>>>() {
+                let _ = self.write_entry(&path, &key, options, &binaries);
+            }
+        }
+
+        return Ok((program, kernels));
+    }
+
+    /// Removes the cache entry of the specified program, returning `true` if it existed.
+    /// Errors computing the program's [key](Self::key_in) are returned as [`Other`](std::io::ErrorKind::Other) io errors.
+    pub fn invalidate_in<C: Context>(
+        &self,
+        ctx: &C,
+        source: impl AsRef<str>,
+        options: Option<&str>,
+    ) -> std::io::Result<bool> {
+        let key = Self::key_in(ctx, source.as_ref(), options)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        self.invalidate(key)
+    }
+
+    /// Removes the cache entry with the specified [key](Self::key_in), returning `true` if it existed.
+    pub fn invalidate(&self, key: u64) -> std::io::Result<bool> {
+        match std::fs::remove_file(self.entry_path(key)) {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Removes every entry of the cache.
+    pub fn clear(&self) -> std::io::Result<()> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(x) => x,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().map_or(false, |x| x == "bin") {
+                std::fs::remove_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the hash of the key of the specified program for the devices of the context, which names its cache entry.
+    #[inline]
+    pub fn key_in<C: Context>(ctx: &C, source: &str, options: Option<&str>) -> Result<u64> {
+        full_key_in(ctx, source, options).map(|key| hash_key(&key))
+    }
+
+    #[inline]
+    fn entry_path(&self, key: u64) -> PathBuf {
+        self.dir.join(format!("{key:016x}.bin"))
+    }
+
+    fn write_entry(
+        &self,
+        path: &Path,
+        key: &[u8],
+        options: Option<&str>,
+        binaries: &[Vec<u8>],
+    ) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        // Write to a temporary file first, so that concurrent readers never see a partial entry
+        let tmp = path.with_extension(format!(
+            "tmp{}-{}",
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut file = File::create(&tmp)?;
+        file.write_all(MAGIC)?;
+        file.write_all(&u64::try_from(key.len()).unwrap().to_le_bytes())?;
+        file.write_all(key)?;
+        match options {
+            Some(options) => {
+                file.write_all(&[1])?;
+                file.write_all(&u64::try_from(options.len()).unwrap().to_le_bytes())?;
+                file.write_all(options.as_bytes())?;
+            }
+            None => file.write_all(&[0])?,
+        }
+        file.write_all(&u32::try_from(binaries.len()).unwrap().to_le_bytes())?;
+        for binary in binaries {
+            file.write_all(&u64::try_from(binary.len()).unwrap().to_le_bytes())?;
+            file.write_all(binary)?;
+        }
+        file.sync_all()?;
+        drop(file);
+
+        std::fs::rename(tmp, path)
+    }
+}
+
+impl RawProgram {
+    /// Builds a program from source, using the [global cache](ProgramCache::global) if it's enabled.
+    #[inline(always)]
+    pub fn from_source_cached(
+        source: impl AsRef<str>,
+        options: Option<&str>,
+    ) -> Result<(Self, Box<[RawKernel]>)> {
+        Self::from_source_cached_in(Global::get(), source, options)
+    }
+
+    /// Builds a program from source, using the [global cache](ProgramCache::global) if it's enabled.
+    #[inline]
+    pub fn from_source_cached_in<C: Context>(
+        ctx: &C,
+        source: impl AsRef<str>,
+        options: Option<&str>,
+    ) -> Result<(Self, Box<[RawKernel]>)> {
+        match ProgramCache::global() {
+            Some(cache) => cache.load_or_build_in(ctx, source, options),
+            None => Self::from_source_in(ctx, source, options),
+        }
+    }
+}
+
+/// Returns the full key of the specified program for the devices of the context.
+fn full_key_in<C: Context>(ctx: &C, source: &str, options: Option<&str>) -> Result<Vec<u8>> {
+    let mut key = Vec::new();
+    key.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    write_key_str(&mut key, source);
+    write_key_str(&mut key, options.unwrap_or_default());
+
+    for device in ctx.as_raw().devices()? {
+        let platform = device.platform()?;
+        write_key_str(&mut key, &device.name()?);
+        write_key_str(&mut key, &device.driver_version_string()?);
+        write_key_str(&mut key, &platform.name()?);
+        write_key_str(&mut key, &platform.version()?);
+    }
+
+    Ok(key)
+}
+
+#[inline]
+fn write_key_str(key: &mut Vec<u8>, v: &str) {
+    key.extend_from_slice(&(v.len() as u64).to_le_bytes());
+    key.extend_from_slice(v.as_bytes());
+}
+
+#[inline]
+fn hash_key(key: &[u8]) -> u64 {
+    let mut hasher = Fnv1a::new();
+    hasher.write(key);
+    hasher.finish()
+}
+
+/// Reads the build options and binaries of a cache entry, if its key matches.
+fn read_entry(path: &Path, key: &[u8]) -> Option<(Option<String>, Vec<Vec<u8>>)> {
+    let mut file = File::open(path).ok()?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).ok()?;
+
+    let mut bytes = bytes.strip_prefix(MAGIC)?;
+    let mut take = |len: usize| -> Option<&[u8]> {
+        if bytes.len() < len {
+            return None;
+        }
+        let (result, rest) = bytes.split_at(len);
+        bytes = rest;
+        Some(result)
+    };
+
+    let key_len = u64::from_le_bytes(take(8)?.try_into().ok()?);
+    if take(usize::try_from(key_len).ok()?)? != key {
+        return None;
+    }
+
+    let options = match take(1)? {
+        [0] => None,
+        [1] => {
+            let len = u64::from_le_bytes(take(8)?.try_into().ok()?);
+            let options = take(usize::try_from(len).ok()?)?;
+            Some(String::from_utf8(options.to_vec()).ok()?)
+        }
+        _ => return None,
+    };
+
+    let count = u32::from_le_bytes(take(4)?.try_into().ok()?);
+    let mut result = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let len = u64::from_le_bytes(take(8)?.try_into().ok()?);
+        result.push(take(usize::try_from(len).ok()?)?.to_vec());
+    }
+
+    Some((options, result))
+}
+
+/// FNV-1a hasher. Unlike [`DefaultHasher`](std::collections::hash_map::DefaultHasher), its output is stable between Rust versions.
+pub(crate) struct Fnv1a(u64);
+
+impl Fnv1a {
+    #[inline(always)]
+    pub(crate) const fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    #[inline]
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn write_u32(&mut self, v: u32) {
+        self.write(&v.to_le_bytes())
+    }
+
+    #[inline]
+    pub(crate) fn write_str(&mut self, v: &str) {
+        self.write(&(v.len() as u64).to_le_bytes());
+        self.write(v.as_bytes())
+    }
+
+    #[inline(always)]
+    pub(crate) const fn finish(&self) -> u64 {
+        self.0
+    }
+}
@@ -0,0 +1,293 @@
+use super::*;
+use std::{fmt::Display, sync::Arc};
+
+/// Severity of a [`BuildMessage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    Note,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    #[inline]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// Message emitted by the OpenCL C compiler.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BuildMessage {
+    /// File reported by the compiler, like `<source>` or `input.cl`.
+    pub file: Option<String>,
+    /// Line of the message, starting at 1. It's relative to the source without its [prefix](BuildDiagnostics::with_prefix_lines).
+    pub line: Option<usize>,
+    /// Column of the message, starting at 1.
+    pub column: Option<usize>,
+    pub severity: Severity,
+    pub text: String,
+}
+
+impl BuildMessage {
+    /// Parses a line of a build log, like `<source>:12:5: error: use of undeclared identifier 'x'`.
+    /// Returns `None` if the line isn't the start of a message, like the source excerpts of some compilers.
+    pub fn parse(line: &str) -> Option<Self> {
+        const MARKERS: &[(&str, Severity)] = &[
+            ("fatal error: ", Severity::Error),
+            ("error: ", Severity::Error),
+            ("warning: ", Severity::Warning),
+            ("note: ", Severity::Note),
+        ];
+
+        let line = line.trim_end();
+        for (marker, severity) in MARKERS {
+            let (location, text) = match line.strip_prefix(marker) {
+                Some(text) => ("", text),
+                None => match line.find(&format!(": {marker}")) {
+                    Some(idx) => (&line[..idx], &line[idx + marker.len() + 2..]),
+                    None => continue,
+                },
+            };
+
+            // `file:line:column`, where the file may contain colons
+            let mut parts = location.rsplitn(3, ':').collect::<Vec<_>>();
+            parts.reverse();
+            let (file, line, column) = match parts[..] {
+                [file, line, column] => match (line.trim().parse(), column.trim().parse()) {
+                    (Ok(line), Ok(column)) => (file, Some(line), Some(column)),
+                    _ => match column.trim().parse() {
+                        Ok(line) => (location.rsplit_once(':').unwrap().0, Some(line), None),
+                        Err(_) => (location, None, None),
+                    },
+                },
+                [file, line] => match line.trim().parse() {
+                    Ok(line) => (file, Some(line), None),
+                    Err(_) => (location, None, None),
+                },
+                _ => (location, None, None),
+            };
+
+            let file = file.trim();
+            return Some(Self {
+                file: (!file.is_empty()).then(|| file.to_string()),
+                line,
+                column,
+                severity: *severity,
+                text: text.trim().to_string(),
+            });
+        }
+
+        None
+    }
+}
+
+/// Build log of a single device.
+#[derive(Debug, Clone)]
+pub struct DeviceDiagnostics {
+    pub device: RawDevice,
+    /// Name of the device.
+    pub name: String,
+    /// Unparsed build log.
+    pub log: String,
+    pub messages: Vec<BuildMessage>,
+}
+
+/// Structured diagnostics of a program's build, with the messages of every device.
+///
+/// Its [`Display`] implementation renders every message alongside a caret-annotated excerpt of the source, and it's the description of the errors returned by failed builds.
+/// The diagnostics of successful builds (like warnings) are returned by [`RawProgram::build_diagnostics`].
+#[derive(Debug, Clone)]
+pub struct BuildDiagnostics {
+    devices: Vec<DeviceDiagnostics>,
+    source: Option<Arc<str>>,
+    prefix_lines: usize,
+}
+
+impl BuildDiagnostics {
+    /// Returns the diagnostics of the last build of the program, for every one of its devices.
+    pub fn new(program: &RawProgram) -> Result<Self> {
+        let mut devices = Vec::new();
+        for device in program.devices()? {
+            let log = program.build_log(&device)?;
+            let messages = log.lines().filter_map(BuildMessage::parse).collect();
+            devices.push(DeviceDiagnostics {
+                name: device.name()?,
+                device,
+                log,
+                messages,
+            });
+        }
+
+        let source = match program.source() {
+            Ok(source) if !source.is_empty() => Some(Arc::from(source)),
+            _ => None,
+        };
+
+        Ok(Self {
+            devices,
+            source,
+            prefix_lines: 0,
+        })
+    }
+
+    /// Maps the line numbers back to the source without its first `lines` lines (like prepended defines).
+    /// Messages inside the prefix lose their line.
+    pub fn with_prefix_lines(mut self, lines: usize) -> Self {
+        let delta = lines as isize - self.prefix_lines as isize;
+        for message in self.devices.iter_mut().flat_map(|x| x.messages.iter_mut()) {
+            message.line = message
+                .line
+                .and_then(|line| line.checked_add_signed(-delta))
+                .filter(|line| *line > 0);
+        }
+
+        self.prefix_lines = lines;
+        self
+    }
+
+    /// Returns the diagnostics of every device.
+    #[inline(always)]
+    pub fn devices(&self) -> &[DeviceDiagnostics] {
+        &self.devices
+    }
+
+    /// Returns the messages of every device.
+    #[inline]
+    pub fn messages(&self) -> impl Iterator<Item = &BuildMessage> {
+        self.devices.iter().flat_map(|x| x.messages.iter())
+    }
+
+    /// Returns the warnings of every device.
+    #[inline]
+    pub fn warnings(&self) -> impl Iterator<Item = &BuildMessage> {
+        self.messages().filter(|x| x.severity == Severity::Warning)
+    }
+
+    /// Returns `true` if any device reported an error.
+    #[inline]
+    pub fn has_errors(&self) -> bool {
+        self.messages().any(|x| x.severity == Severity::Error)
+    }
+
+    /// Returns `true` if no device has a build log.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.devices.iter().all(|x| x.log.trim().is_empty())
+    }
+
+    /// Returns the line of the source (without its prefix), if available.
+    /// The `#line` directives of the source (like the ones of a [`Preprocessor`]) are followed,
+    /// with filename-less directives and the ones of [`Preprocessor::ROOT_NAME`] referring to the root file.
+    fn source_line(&self, file: Option<&str>, line: usize) -> Option<&str> {
+        let source = self.source.as_deref()?;
+        let directives = source
+            .lines()
+            .filter_map(line_directive)
+            .map(|(line, file)| (line, root_file(file)));
+
+        let file = root_file(file).filter(|file| directives.clone().any(|x| x.1 == Some(*file)));
+        let line = match file {
+            Some(_) => line,
+            None => line.checked_add(self.prefix_lines)?,
+        };
+
+        let mut current = (None, 1);
+        for source_line in source.lines() {
+            if let Some((next, next_file)) = line_directive(source_line) {
+                current = (root_file(next_file), next);
+                continue;
+            }
+
+            if current == (file, line) {
+                return Some(source_line);
+            }
+            current.1 += 1;
+        }
+
+        None
+    }
+
+    fn fmt_message(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        message: &BuildMessage,
+    ) -> std::fmt::Result {
+        writeln!(f, "{}: {}", message.severity.as_str(), message.text)?;
+
+        let line = match message.line {
+            Some(line) => line,
+            None => return Ok(()),
+        };
+
+        let file = message.file.as_deref().unwrap_or(Preprocessor::ROOT_NAME);
+        let width = line.to_string().len();
+        match message.column {
+            Some(column) => writeln!(f, "{:width$}--> {file}:{line}:{column}", "")?,
+            None => writeln!(f, "{:width$}--> {file}:{line}", "")?,
+        }
+
+        let source = match self.source_line(message.file.as_deref(), line) {
+            Some(source) => source,
+            None => return Ok(()),
+        };
+
+        writeln!(f, "{:width$} |", "")?;
+        writeln!(f, "{line} | {source}")?;
+        if let Some(column) = message.column {
+            // keep the tabs of the source, so that the caret is aligned
+            let padding = source
+                .chars()
+                .take(column.saturating_sub(1))
+                .map(|x| if x == '\t' { '\t' } else { ' ' })
+                .collect::<String>();
+            writeln!(f, "{:width$} | {padding}^", "")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for BuildDiagnostics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for device in self.devices.iter().filter(|x| !x.log.trim().is_empty()) {
+            writeln!(f, "device `{}`:", device.name)?;
+            if device.messages.is_empty() {
+                writeln!(f, "{}", device.log.trim_end())?;
+                continue;
+            }
+
+            for message in device.messages.iter() {
+                self.fmt_message(f, message)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Maps the name of the root file to `None`.
+#[inline]
+fn root_file(file: Option<&str>) -> Option<&str> {
+    file.filter(|x| *x != Preprocessor::ROOT_NAME)
+}
+
+/// Parses a `#line` directive, like `#line 12 "common.cl"`.
+#[inline]
+fn line_directive(line: &str) -> Option<(usize, Option<&str>)> {
+    let rest = line.trim_start().strip_prefix('#')?.trim_start();
+    let mut parts = rest
+        .strip_prefix("line")?
+        .trim()
+        .splitn(2, char::is_whitespace);
+
+    let line = parts.next()?.parse().ok()?;
+    let file = parts
+        .next()
+        .and_then(|x| x.trim().strip_prefix('"')?.strip_suffix('"'));
+    Some((line, file))
+}
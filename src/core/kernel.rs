@@ -1,6 +1,9 @@
 use crate::{
     context::{Context, RawContext},
-    core::*,
+    core::{
+        tuning::{KernelKey, LocalSize},
+        *,
+    },
     event::{
         consumer::{NoopEvent, PhantomEvent},
         RawEvent,
@@ -48,6 +51,33 @@ impl RawKernel {
         Ok(())
     }
 
+    /// Creates a new kernel object for the kernel `name` of `program`.
+    pub fn new(program: &RawProgram, name: &str) -> Result<Self> {
+        let name =
+            std::ffi::CString::new(name).map_err(|e| Error::new(ErrorKind::InvalidValue, e))?;
+
+        let mut err = 0;
+        let id = unsafe { clCreateKernel(program.id(), name.as_ptr(), &mut err) };
+        if err != 0 {
+            return Err(Error::from(err));
+        }
+
+        Ok(unsafe { Self::from_id(id).unwrap() })
+    }
+
+    /// Creates a copy of the kernel object, with the same arguments as the original.
+    /// Unlike [`clone`](Clone::clone), the new kernel object is independent of this one.
+    #[docfg(feature = "cl2_1")]
+    pub fn clone_kernel(&self) -> Result<Self> {
+        let mut err = 0;
+        let id = unsafe { opencl_sys::clCloneKernel(self.id(), &mut err) };
+        if err != 0 {
+            return Err(Error::from(err));
+        }
+
+        Ok(unsafe { Self::from_id(id).unwrap() })
+    }
+
     #[inline(always)]
     pub unsafe fn set_argument<T: Copy, R: Borrow<T>>(&mut self, idx: u32, v: R) -> Result<()> {
         let ptr = v.borrow() as *const _ as *const _;
@@ -90,70 +120,64 @@ impl RawKernel {
         Ok(())
     }
 
+    /// Enqueues the kernel over `range`. Unless `local_work_dims` is [`Default`](LocalSize::Default), it overrides the local size of the range.
     #[inline(always)]
     pub unsafe fn enqueue_unchecked<const N: usize>(
         &mut self,
         queue: &RawCommandQueue,
-        global_work_dims: [usize; N],
-        local_work_dims: impl Into<Option<[usize; N]>>,
+        range: impl Into<NdRange<N>>,
+        local_work_dims: impl Into<LocalSize<N>>,
         wait: WaitList,
     ) -> Result<RawEvent> {
-        let work_dim = u32::try_from(N).expect("Integer overflow");
-        let local_work_dims = local_work_dims.into();
-        let local_work_dims = match local_work_dims {
-            Some(x) => x.as_ptr(),
-            None => core::ptr::null(),
-        };
+        self.enqueue_with_key(queue, range, local_work_dims, None, wait)
+    }
 
+    /// Enqueues the kernel over `range`, tuning [`Auto`](LocalSize::Auto) local sizes with the specified key.
+    #[inline]
+    pub(crate) unsafe fn enqueue_with_key<const N: usize>(
+        &mut self,
+        queue: &RawCommandQueue,
+        range: impl Into<NdRange<N>>,
+        local_work_dims: impl Into<LocalSize<N>>,
+        key: Option<&KernelKey>,
+        wait: WaitList,
+    ) -> Result<RawEvent> {
+        let range = local_work_dims
+            .into()
+            .resolve(self, key, queue, range.into(), wait)?;
         let (num_events_in_wait_list, event_wait_list) = wait_list(wait)?;
-
-        let mut event = core::ptr::null_mut();
-        tri!(clEnqueueNDRangeKernel(
-            queue.id(),
-            self.id(),
-            work_dim,
-            core::ptr::null(),
-            global_work_dims.as_ptr(),
-            local_work_dims,
-            num_events_in_wait_list,
-            event_wait_list,
-            addr_of_mut!(event)
-        ));
-
-        Ok(RawEvent::from_id(event).unwrap())
+        self.enqueue_range(queue, &range, num_events_in_wait_list, event_wait_list)
     }
 
+    /// Enqueues the kernel over `range` inside `scope`. Unless `local_work_dims` is [`Default`](LocalSize::Default), it overrides the local size of the range.
     #[inline(always)]
     pub unsafe fn enqueue_with_scope<'scope, 'env, C: Context, const N: usize>(
         &mut self,
         scope: &'scope Scope<'scope, 'env, C>,
-        global_work_dims: [usize; N],
-        local_work_dims: impl Into<Option<[usize; N]>>,
+        range: impl Into<NdRange<N>>,
+        local_work_dims: impl Into<LocalSize<N>>,
         wait: WaitList,
     ) -> Result<NoopEvent> {
-        let work_dim = u32::try_from(N).expect("Integer overflow");
-        let local_work_dims = local_work_dims.into();
-        let local_work_dims = match local_work_dims {
-            Some(x) => x.as_ptr(),
-            None => core::ptr::null(),
-        };
+        self.enqueue_with_scope_and_key(scope, range, local_work_dims, None, wait)
+    }
 
+    /// Enqueues the kernel over `range` inside `scope`, tuning [`Auto`](LocalSize::Auto) local sizes with the specified key.
+    #[inline]
+    pub(crate) unsafe fn enqueue_with_scope_and_key<'scope, 'env, C: Context, const N: usize>(
+        &mut self,
+        scope: &'scope Scope<'scope, 'env, C>,
+        range: impl Into<NdRange<N>>,
+        local_work_dims: impl Into<LocalSize<N>>,
+        key: Option<&KernelKey>,
+        wait: WaitList,
+    ) -> Result<NoopEvent> {
+        let range = range.into();
+        let local_work_dims = local_work_dims.into();
         let (num_events_in_wait_list, event_wait_list) = wait_list(wait)?;
 
         return scope.enqueue_noop(|queue| {
-            let mut event = core::ptr::null_mut();
-            tri!(clEnqueueNDRangeKernel(
-                queue.id(),
-                self.id(),
-                work_dim,
-                core::ptr::null(),
-                global_work_dims.as_ptr(),
-                local_work_dims,
-                num_events_in_wait_list,
-                event_wait_list,
-                addr_of_mut!(event)
-            ));
-            return Ok(RawEvent::from_id(event).unwrap());
+            let range = local_work_dims.resolve(self, key, queue, range, wait)?;
+            self.enqueue_range(queue, &range, num_events_in_wait_list, event_wait_list)
         });
     }
 
@@ -167,12 +191,12 @@ impl RawKernel {
     >(
         &mut self,
         scope: &'scope Scope<'scope, 'env, C>,
-        global_work_dims: [usize; N],
-        local_work_dims: impl Into<Option<[usize; N]>>,
+        range: impl Into<NdRange<N>>,
+        local_work_dims: impl Into<LocalSize<N>>,
         wait: WaitList,
     ) -> Result<PhantomEvent<T>> {
         Ok(self
-            .enqueue_with_scope(scope, global_work_dims, local_work_dims, wait)?
+            .enqueue_with_scope(scope, range, local_work_dims, wait)?
             .set_consumer(PhantomData))
     }
 
@@ -216,6 +240,56 @@ impl RawKernel {
         }
     }
 
+    /// Maximum work-group size that can be used to execute the kernel on `device`.
+    #[inline(always)]
+    pub fn work_group_size(&self, device: &RawDevice) -> Result<usize> {
+        self.get_work_group_info(device, CL_KERNEL_WORK_GROUP_SIZE)
+    }
+
+    /// Preferred multiple of the work-group size to execute the kernel on `device`. This is a performance hint.
+    #[docfg(feature = "cl1_1")]
+    #[inline(always)]
+    pub fn preferred_work_group_size_multiple(&self, device: &RawDevice) -> Result<usize> {
+        self.get_work_group_info(device, CL_KERNEL_PREFERRED_WORK_GROUP_SIZE_MULTIPLE)
+    }
+
+    unsafe fn enqueue_range<const N: usize>(
+        &mut self,
+        queue: &RawCommandQueue,
+        range: &NdRange<N>,
+        num_events_in_wait_list: u32,
+        event_wait_list: *const cl_event,
+    ) -> Result<RawEvent> {
+        let work_dim = u32::try_from(N).expect("Integer overflow");
+        let local_work_dims = match range.local {
+            Some(ref x) => x.as_ptr(),
+            None => core::ptr::null(),
+        };
+
+        // OpenCL 1.0 only accepts null offsets
+        let global_work_offset = match range.offset.iter().all(|x| *x == 0) {
+            true => core::ptr::null(),
+            false => range.offset.as_ptr(),
+        };
+
+        let mut event = core::ptr::null_mut();
+        tri!(clEnqueueNDRangeKernel(
+            queue.id(),
+            self.id(),
+            work_dim,
+            global_work_offset,
+            range.global.as_ptr(),
+            local_work_dims,
+            num_events_in_wait_list,
+            event_wait_list,
+            addr_of_mut!(event)
+        ));
+
+        let event = RawEvent::from_id(event).unwrap();
+        crate::context::label_kernel_event(&event, self);
+        Ok(event)
+    }
+
     #[inline]
     fn get_info_string(&self, ty: cl_kernel_info) -> Result<String> {
         unsafe {
@@ -242,6 +316,27 @@ impl RawKernel {
         }
     }
 
+    #[inline]
+    fn get_work_group_info<T: Copy>(
+        &self,
+        device: &RawDevice,
+        ty: cl_kernel_work_group_info,
+    ) -> Result<T> {
+        let mut value = MaybeUninit::<T>::uninit();
+
+        unsafe {
+            tri!(clGetKernelWorkGroupInfo(
+                self.id(),
+                device.id(),
+                ty,
+                core::mem::size_of::<T>(),
+                value.as_mut_ptr().cast(),
+                core::ptr::null_mut()
+            ));
+            Ok(value.assume_init())
+        }
+    }
+
     #[inline]
     fn get_info<T: Copy>(&self, ty: cl_kernel_info) -> Result<T> {
         let mut value = MaybeUninit::<T>::uninit();
@@ -280,6 +375,7 @@ unsafe impl Sync for RawKernel {}
 #[cfg(feature = "cl1_2")]
 use {
     crate::buffer::flags::MemAccess,
+    crate::signature::{Arg, Element, Scalar},
     opencl_sys::{
         clGetKernelArgInfo, cl_kernel_arg_info, CL_KERNEL_ARG_ACCESS_QUALIFIER,
         CL_KERNEL_ARG_ADDRESS_QUALIFIER, CL_KERNEL_ARG_NAME, CL_KERNEL_ARG_TYPE_NAME,
@@ -321,8 +417,10 @@ impl RawKernel {
 
     /// Returns the type qualifier specified for the argument given by ```idx```.
     #[inline(always)]
-    pub fn arg_qualifier(&self, idx: u32) -> Result<String> {
-        self.get_arg_info(CL_KERNEL_ARG_TYPE_QUALIFIER, idx)
+    pub fn arg_qualifier(&self, idx: u32) -> Result<TypeQualifier> {
+        let bits =
+            self.get_arg_info::<cl_kernel_arg_type_qualifier>(CL_KERNEL_ARG_TYPE_QUALIFIER, idx)?;
+        Ok(TypeQualifier::from_bits_truncate(bits))
     }
 
     /// Returns the name specified for the argument given by ```idx```.
@@ -331,6 +429,71 @@ impl RawKernel {
         self.get_arg_info_string(CL_KERNEL_ARG_NAME, idx)
     }
 
+    /// Compares the kernel's arguments with the ones declared in Rust, using the kernel's argument information.
+    /// Returns an error naming the first argument that doesn't match.
+    pub fn validate_signature(&self, args: &[Arg]) -> Result<()> {
+        let name = self.name()?;
+        let num_args = self.num_args()? as usize;
+        if num_args != args.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidKernelArgs,
+                format!(
+                    "kernel `{name}` has {num_args} arguments, but {} were declared in Rust",
+                    args.len()
+                ),
+            ));
+        }
+
+        for (i, arg) in args.iter().enumerate() {
+            let idx = i as u32;
+            let ty = self.arg_type_name(idx)?;
+            let addr = self.arg_address_qualifier(idx)?;
+            let is_const = addr == AddrQualifier::Constant
+                || self.arg_qualifier(idx)?.contains(TypeQualifier::CONST);
+
+            let (pointer, element) = match ty.strip_suffix('*') {
+                Some(base) => (true, Element::from_c_name(base.trim_end())),
+                None => (false, Element::from_c_name(&ty)),
+            };
+            let element = element.unwrap_or(Element::new(Scalar::Unknown, 1));
+
+            let matches = match *arg {
+                Arg::Value(rust) => !pointer && rust.is_compatible(element),
+                Arg::Pointer { mutable, ty: rust } => {
+                    pointer
+                        && matches!(addr, AddrQualifier::Global | AddrQualifier::Constant)
+                        && mutable != is_const
+                        && rust.is_compatible(element)
+                }
+                Arg::Local(rust) => {
+                    pointer && addr == AddrQualifier::Local && rust.is_compatible(element)
+                }
+                Arg::Other => true,
+            };
+
+            if !matches {
+                let space = match addr {
+                    _ if !pointer => "",
+                    AddrQualifier::Global => "__global ",
+                    AddrQualifier::Local => "__local ",
+                    AddrQualifier::Constant => "__constant ",
+                    AddrQualifier::Private => "__private ",
+                };
+                let constness = match is_const && pointer && addr != AddrQualifier::Constant {
+                    true => "const ",
+                    false => "",
+                };
+
+                return Err(Error::new(
+                    ErrorKind::InvalidKernelArgs,
+                    format!("argument {i} of kernel `{name}` is `{constness}{space}{ty}` in OpenCL, but `{arg}` in Rust"),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
     #[inline]
     fn get_arg_info_string(&self, ty: cl_kernel_arg_info, idx: u32) -> Result<String> {
         unsafe {
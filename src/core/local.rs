@@ -0,0 +1,109 @@
+use super::*;
+use std::marker::PhantomData;
+
+/// A `__local` memory kernel argument, holding `len` elements of type `T`.
+/// The memory is allocated by the device for every work-group, and only lives for the duration of the kernel.
+///
+/// In `#[blaze]` kernels, local memory arguments are declared as `local<[T]>`.
+/// (`local [T]` isn't valid Rust syntax, and the `extern` block must parse before the macro sees it.)
+/// ```rust,ignore
+/// #[blaze(Reduce)]
+/// #[link = include_str!("reduce.cl")]
+/// extern "C" {
+///     fn reduce(n: u32, input: *const f32, output: *mut f32, scratch: local<[f32]>);
+/// }
+///
+/// let scratch = LocalMem::<f32>::new(256);
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct LocalMem<T> {
+    len: usize,
+    phtm: PhantomData<T>,
+}
+
+impl<T> LocalMem<T> {
+    /// Creates a new local memory argument with space for `len` elements.
+    #[inline(always)]
+    pub const fn new(len: usize) -> Self {
+        Self {
+            len,
+            phtm: PhantomData,
+        }
+    }
+
+    /// Number of elements of the local memory.
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the local memory has no elements.
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Size in bytes of the local memory, or [`None`] if it overflows a `usize`.
+    #[inline(always)]
+    pub const fn size(&self) -> Option<usize> {
+        self.len.checked_mul(core::mem::size_of::<T>())
+    }
+
+    /// Allocates the local memory as the argument `idx` of the kernel.
+    /// Returns an error if the local memory is empty, or if it's bigger than the local memory of any of the kernel's devices.
+    #[inline]
+    pub unsafe fn set_arg(&self, kernel: &mut RawKernel, idx: u32) -> Result<()> {
+        let max = min_local_mem_size(kernel)?;
+        self.set_arg_with_max(kernel, idx, max)
+    }
+
+    /// Allocates the local memory as the argument `idx` of the kernel, checking it against `max` bytes of local memory
+    /// (like the cached [`KernelPool::local_mem_size`]) instead of querying the kernel's devices.
+    pub unsafe fn set_arg_with_max(
+        &self,
+        kernel: &mut RawKernel,
+        idx: u32,
+        max: u64,
+    ) -> Result<()> {
+        let size = match self.size() {
+            Some(0) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidArgSize,
+                    "local memory arguments cannot be empty",
+                ))
+            }
+            Some(size) if size as u64 <= max => size,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidArgSize,
+                    format!(
+                        "local memory argument of {} elements of {} bytes exceeds the {max} bytes of local memory of the kernel's devices",
+                        self.len,
+                        core::mem::size_of::<T>()
+                    ),
+                ))
+            }
+        };
+
+        kernel.allocate_argument(idx, size)
+    }
+}
+
+/// Returns the smallest local memory size of the kernel's devices.
+/// The minimum guaranteed by the specification depends on the device type (only 1 KiB for custom devices), so it's always queried.
+pub(crate) fn min_local_mem_size(kernel: &RawKernel) -> Result<u64> {
+    let mut min = u64::MAX;
+    for device in kernel.raw_context()?.devices()? {
+        min = min.min(device.local_mem_size()?.get());
+    }
+    Ok(min)
+}
+
+impl<T> Clone for LocalMem<T> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for LocalMem<T> {}
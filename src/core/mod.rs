@@ -1,7 +1,12 @@
-flat_mod!(error, platform, program, queue, kernel);
+flat_mod!(error, platform, program, cache, queue, kernel, local, arg, range, diagnostics, options, preprocessor, pool, specialize, sampler);
 
 pub mod device;
 pub use device::RawDevice;
 
+/// OpenCL vector types
+pub mod vector;
+/// Local work-group size auto-tuning
+pub mod tuning;
+
 #[cfg(feature = "cl2")]
 flat_mod!(pipe);
\ No newline at end of file
@@ -0,0 +1,148 @@
+use super::device::Version;
+use std::{fmt::Display, path::Path};
+
+/// Builder of the options passed to the OpenCL C compiler.
+/// ```rust,ignore
+/// let options = BuildOptions::new()
+///     .define("USE_DOUBLE")
+///     .define_value("TILE", 16)
+///     .std(Version::CL1_2)
+///     .fast_math();
+///
+/// let (program, kernels) = RawProgram::from_source(source, Some(options.as_str()))?;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BuildOptions {
+    options: String,
+}
+
+impl BuildOptions {
+    /// Creates a new builder without any options.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            options: String::new(),
+        }
+    }
+
+    /// Defines the macro `name`, like `#define name`.
+    /// # Panics
+    /// This method panics if `name` isn't a valid identifier.
+    #[inline]
+    pub fn define(self, name: impl AsRef<str>) -> Self {
+        let name = name.as_ref();
+        assert_identifier(name);
+        self.option(format!("-D {name}"))
+    }
+
+    /// Defines the macro `name` with the specified value, like `#define name value`.
+    /// # Panics
+    /// This method panics if `name` isn't a valid identifier, or if `value` is empty or contains whitespace.
+    #[inline]
+    pub fn define_value(self, name: impl AsRef<str>, value: impl Display) -> Self {
+        let name = name.as_ref();
+        let value = value.to_string();
+        assert_identifier(name);
+        assert!(
+            !value.is_empty() && !value.contains(char::is_whitespace),
+            "the value of `{name}` cannot be empty or contain whitespace"
+        );
+
+        self.option(format!("-D {name}={value}"))
+    }
+
+    /// Sets the version of OpenCL C used to compile the program (`-cl-std`).
+    #[inline]
+    pub fn std(self, version: Version) -> Self {
+        self.option(format!("-cl-std=CL{}.{}", version.major(), version.minor()))
+    }
+
+    /// Allows optimizations that may violate IEEE 754 (`-cl-fast-relaxed-math`).
+    #[inline(always)]
+    pub fn fast_math(self) -> Self {
+        self.option("-cl-fast-relaxed-math")
+    }
+
+    /// Allows single and double precision denormalized numbers to be flushed to zero (`-cl-denorms-are-zero`).
+    #[inline(always)]
+    pub fn denormals_are_zero(self) -> Self {
+        self.option("-cl-denorms-are-zero")
+    }
+
+    /// Makes all warnings into errors (`-Werror`).
+    #[inline(always)]
+    pub fn warnings_as_errors(self) -> Self {
+        self.option("-Werror")
+    }
+
+    /// Inhibits all warning messages (`-w`).
+    #[inline(always)]
+    pub fn disable_warnings(self) -> Self {
+        self.option("-w")
+    }
+
+    /// Adds a directory to the list of directories searched for header files (`-I`).
+    /// Not every driver supports include paths, so programs made of several files may be resolved on the host by a [`Preprocessor`](super::Preprocessor) instead.
+    #[inline]
+    pub fn include_dir(self, path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_string_lossy();
+        match path.contains(char::is_whitespace) {
+            true => self.option(format!("-I \"{path}\"")),
+            false => self.option(format!("-I {path}")),
+        }
+    }
+
+    /// Adds a raw option, like `-cl-mad-enable`.
+    #[inline]
+    pub fn option(mut self, option: impl AsRef<str>) -> Self {
+        let option = option.as_ref().trim();
+        if !option.is_empty() {
+            if !self.options.is_empty() {
+                self.options.push(' ');
+            }
+            self.options.push_str(option);
+        }
+        self
+    }
+
+    /// Returns the options, as passed to the compiler.
+    #[inline(always)]
+    pub fn as_str(&self) -> &str {
+        &self.options
+    }
+
+    /// Returns `true` if no options have been set.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.options.is_empty()
+    }
+}
+
+impl AsRef<str> for BuildOptions {
+    #[inline(always)]
+    fn as_ref(&self) -> &str {
+        &self.options
+    }
+}
+
+impl Display for BuildOptions {
+    #[inline(always)]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.options, f)
+    }
+}
+
+impl From<BuildOptions> for String {
+    #[inline(always)]
+    fn from(options: BuildOptions) -> Self {
+        options.options
+    }
+}
+
+#[inline]
+fn assert_identifier(name: &str) {
+    let mut chars = name.chars();
+    let valid = matches!(chars.next(), Some(x) if x == '_' || x.is_ascii_alphabetic())
+        && chars.all(|x| x == '_' || x.is_ascii_alphanumeric());
+    assert!(valid, "`{name}` isn't a valid macro name");
+}
@@ -1,4 +1,4 @@
-use std::{ops::{Deref, DerefMut}, ptr::addr_of_mut, mem::MaybeUninit, num::NonZeroU32};
+use std::{ops::{Deref, DerefMut}, ptr::addr_of_mut, mem::MaybeUninit, num::NonZeroU32, marker::PhantomData};
 use opencl_sys::*;
 use crate::{memobj::RawMemObject, prelude::*, buffer::flags::{MemFlags, HostPtr, MemAccess}};
 
@@ -40,6 +40,16 @@ impl RawPipe {
         self.get_info(CL_PIPE_MAX_PACKETS)
     }
 
+    /// Sets the pipe as the argument `idx` of the kernel, checking that its packets have the size of `T`.
+    pub unsafe fn set_arg<T> (&self, kernel: &mut RawKernel, idx: u32) -> Result<()> {
+        let packet_size = self.packet_size()?.get() as usize;
+        if packet_size != core::mem::size_of::<T>() {
+            return Err(Error::new(ErrorKind::InvalidArgValue, format!("argument {idx} must be a pipe with packets of {} bytes, but its packets are of {packet_size} bytes", core::mem::size_of::<T>())))
+        }
+
+        kernel.set_argument::<cl_mem, _>(idx, self.id_ref())
+    }
+
     fn get_info<T: Copy> (&self, ty: cl_pipe_info) -> Result<T> {
         let mut result = MaybeUninit::<T>::uninit();
         
@@ -64,4 +74,101 @@ impl DerefMut for RawPipe {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.0
     }
-}
\ No newline at end of file
+}
+
+/// Pipe whose packets are of type `T`.
+/// Its packet size is the size of `T`, which is checked against the limits of every device in the context when the pipe is created.
+/// ```rust,ignore
+/// #[blaze(Pipeline)]
+/// #[link = SOURCE]
+/// extern "C" {
+///     fn produce(out: pipe<write, u32>);
+///     fn consume(input: pipe<u32>, out: *mut u32);
+/// }
+///
+/// let mut pipe = Pipe::<u32>::new(1024)?;
+/// unsafe { pipeline.produce_blocking(&mut pipe, [1024], None, None)? };
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "cl2")))]
+#[derive(Debug, Clone)]
+pub struct Pipe<T, C: Context = Global> {
+    inner: RawPipe,
+    ctx: C,
+    phtm: PhantomData<T>
+}
+
+impl<T> Pipe<T> {
+    /// Creates a new pipe that can hold up to `max_packets` packets.
+    #[inline(always)]
+    pub fn new (max_packets: u32) -> Result<Self> {
+        Self::new_in(Global, max_packets)
+    }
+}
+
+impl<T, C: Context> Pipe<T, C> {
+    /// Creates a new pipe that can hold up to `max_packets` packets.
+    /// Returns an error if a device of the context doesn't support pipes, or if its packets can't be as big as `T`.
+    pub fn new_in (ctx: C, max_packets: u32) -> Result<Self> {
+        let packet_size = match u32::try_from(core::mem::size_of::<T>()) {
+            Ok(x) if x > 0 => x,
+            _ => return Err(Error::new(ErrorKind::InvalidPipeSize, "pipe packets must have a size between 1 and `u32::MAX` bytes"))
+        };
+
+        for device in ctx.as_raw().devices()? {
+            #[cfg(feature = "cl3")]
+            if !device.pipe_support()? {
+                return Err(Error::new(ErrorKind::InvalidOperation, format!("device `{}` doesn't support pipes", device.name()?)))
+            }
+
+            match device.pipe_max_packet_size()? {
+                Some(max) if packet_size <= max.get() => {},
+                Some(max) => return Err(Error::new(ErrorKind::InvalidPipeSize, format!("device `{}` supports pipe packets of up to {max} bytes, but the packets are of {packet_size} bytes", device.name()?))),
+                None => return Err(Error::new(ErrorKind::InvalidOperation, format!("device `{}` doesn't support pipes", device.name()?)))
+            }
+        }
+
+        let inner = RawPipe::new_in(ctx.as_raw(), MemAccess::READ_WRITE, false, packet_size, max_packets)?;
+        Ok(Self { inner, ctx, phtm: PhantomData })
+    }
+
+    /// Returns the maximum number of packets of the pipe.
+    #[inline(always)]
+    pub fn max_packets (&self) -> Result<NonZeroU32> {
+        self.inner.max_packets()
+    }
+
+    /// Returns the underlying raw pipe.
+    #[inline(always)]
+    pub fn as_raw (&self) -> &RawPipe {
+        &self.inner
+    }
+
+    /// Returns the context of the pipe.
+    #[inline(always)]
+    pub fn context (&self) -> &C {
+        &self.ctx
+    }
+}
+
+/// Pipes that can be passed to kernels as the `pipe<T>` arguments of `#[blaze]` kernels.
+/// # Safety
+/// [`set_arg`](KernelPipe::set_arg) must only set pipes with packets of type `T`.
+#[cfg_attr(docsrs, doc(cfg(feature = "cl2")))]
+pub unsafe trait KernelPipe<T> {
+    unsafe fn set_arg (&self, kernel: &mut RawKernel, idx: u32) -> Result<()>;
+}
+
+/// The packet size of raw pipes is checked when they're set as an argument.
+unsafe impl<T> KernelPipe<T> for RawPipe {
+    #[inline(always)]
+    unsafe fn set_arg (&self, kernel: &mut RawKernel, idx: u32) -> Result<()> {
+        RawPipe::set_arg::<T>(self, kernel, idx)
+    }
+}
+
+unsafe impl<T, C: Context> KernelPipe<T> for Pipe<T, C> {
+    #[inline(always)]
+    unsafe fn set_arg (&self, kernel: &mut RawKernel, idx: u32) -> Result<()> {
+        kernel.set_argument::<cl_mem, _>(idx, self.inner.id_ref())
+    }
+}
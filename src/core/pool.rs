@@ -0,0 +1,194 @@
+use super::{
+    tuning::{KernelKey, LocalSize},
+    *,
+};
+use crate::{
+    context::{Context, Scope},
+    event::{consumer::PhantomEvent, RawEvent},
+    WaitList,
+};
+use crossbeam::queue::SegQueue;
+use once_cell::sync::OnceCell;
+use std::{
+    fmt::Debug,
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+};
+
+/// Pool of kernel objects for the same kernel, so it can be launched from several threads at once.
+///
+/// Kernel objects aren't thread-safe while their arguments are set, so every concurrent launch takes its own instance from the pool.
+/// New instances are cloned from the original kernel with `clCloneKernel` on OpenCL 2.1+, or created from its program with `clCreateKernel` otherwise.
+///
+/// The [tuning key](KernelKey) and the [local memory size](KernelPool::local_mem_size) of the kernel are computed the first time they're needed, and shared by every instance.
+pub struct KernelPool {
+    kernel: RawKernel,
+    key: OnceCell<KernelKey>,
+    local_mem_size: OnceCell<u64>,
+    #[cfg(not(feature = "cl2_1"))]
+    program: RawProgram,
+    #[cfg(not(feature = "cl2_1"))]
+    name: String,
+    idle: SegQueue<RawKernel>,
+}
+
+impl KernelPool {
+    /// Creates a new pool for `kernel`. The original kernel object is only used as a template for the new instances.
+    pub fn new(kernel: RawKernel) -> Result<Self> {
+        Ok(Self {
+            #[cfg(not(feature = "cl2_1"))]
+            program: kernel.program()?,
+            #[cfg(not(feature = "cl2_1"))]
+            name: kernel.name()?,
+            kernel,
+            key: OnceCell::new(),
+            local_mem_size: OnceCell::new(),
+            idle: SegQueue::new(),
+        })
+    }
+
+    /// Returns the original kernel object.
+    #[inline(always)]
+    pub fn kernel(&self) -> &RawKernel {
+        &self.kernel
+    }
+
+    /// Takes an idle kernel object from the pool, creating a new one if none are available.
+    /// The kernel object is returned to the pool when the guard is dropped.
+    #[inline]
+    pub fn get(&self) -> Result<PooledKernel<'_>> {
+        let kernel = match self.idle.pop() {
+            Some(x) => x,
+            None => self.create()?,
+        };
+
+        return Ok(PooledKernel {
+            pool: self,
+            kernel: ManuallyDrop::new(kernel),
+        });
+    }
+
+    /// Returns the [tuning key](KernelKey) of the kernel, computing it if needed.
+    #[inline]
+    pub fn key(&self) -> Result<&KernelKey> {
+        self.key.get_or_try_init(|| KernelKey::new(&self.kernel))
+    }
+
+    /// Returns the smallest local memory size, in bytes, of the kernel's devices, querying it if needed.
+    #[inline]
+    pub fn local_mem_size(&self) -> Result<u64> {
+        self.local_mem_size
+            .get_or_try_init(|| local::min_local_mem_size(&self.kernel))
+            .copied()
+    }
+
+    /// Returns the number of idle kernel objects in the pool.
+    #[inline(always)]
+    pub fn idle_count(&self) -> usize {
+        self.idle.len()
+    }
+
+    #[cfg(feature = "cl2_1")]
+    #[inline(always)]
+    fn create(&self) -> Result<RawKernel> {
+        self.kernel.clone_kernel()
+    }
+
+    #[cfg(not(feature = "cl2_1"))]
+    #[inline(always)]
+    fn create(&self) -> Result<RawKernel> {
+        RawKernel::new(&self.program, &self.name)
+    }
+}
+
+impl Debug for KernelPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KernelPool")
+            .field("kernel", &self.kernel)
+            .field("idle", &self.idle.len())
+            .finish()
+    }
+}
+
+/// Kernel object taken from a [`KernelPool`], which is returned to it when dropped.
+#[derive(Debug)]
+pub struct PooledKernel<'a> {
+    pool: &'a KernelPool,
+    kernel: ManuallyDrop<RawKernel>,
+}
+
+impl<'a> PooledKernel<'a> {
+    /// Enqueues the kernel like [`RawKernel::enqueue_unchecked`], tuning [`Auto`](LocalSize::Auto) local sizes with the key of the pool.
+    #[inline]
+    pub unsafe fn enqueue_unchecked<const N: usize>(
+        &mut self,
+        queue: &RawCommandQueue,
+        range: impl Into<NdRange<N>>,
+        local_work_dims: impl Into<LocalSize<N>>,
+        wait: WaitList,
+    ) -> Result<RawEvent> {
+        let local_work_dims = local_work_dims.into();
+        let key = self.key_for(&local_work_dims)?;
+        self.kernel
+            .enqueue_with_key(queue, range, local_work_dims, key, wait)
+    }
+
+    /// Enqueues the kernel like [`RawKernel::enqueue_phantom_with_scope`], tuning [`Auto`](LocalSize::Auto) local sizes with the key of the pool.
+    #[inline]
+    pub unsafe fn enqueue_phantom_with_scope<
+        'scope,
+        'env,
+        T: 'scope,
+        C: Context,
+        const N: usize,
+    >(
+        &mut self,
+        scope: &'scope Scope<'scope, 'env, C>,
+        range: impl Into<NdRange<N>>,
+        local_work_dims: impl Into<LocalSize<N>>,
+        wait: WaitList,
+    ) -> Result<PhantomEvent<T>> {
+        let local_work_dims = local_work_dims.into();
+        let key = self.key_for(&local_work_dims)?;
+        Ok(self
+            .kernel
+            .enqueue_with_scope_and_key(scope, range, local_work_dims, key, wait)?
+            .set_consumer(PhantomData))
+    }
+
+    #[inline]
+    fn key_for<const N: usize>(
+        &self,
+        local_work_dims: &LocalSize<N>,
+    ) -> Result<Option<&'a KernelKey>> {
+        match local_work_dims {
+            LocalSize::Auto(_) => self.pool.key().map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl Deref for PooledKernel<'_> {
+    type Target = RawKernel;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.kernel
+    }
+}
+
+impl DerefMut for PooledKernel<'_> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.kernel
+    }
+}
+
+impl Drop for PooledKernel<'_> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        let kernel = unsafe { ManuallyDrop::take(&mut self.kernel) };
+        self.pool.idle.push(kernel);
+    }
+}
@@ -0,0 +1,262 @@
+use super::*;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+/// Host-side resolver of `#include` directives, for programs made of several files.
+///
+/// Included files are searched relative to the including file, then in the virtual files and then in the include directories.
+/// The contents of every file are delimited by `#line` directives, so the compiler reports the original file and line of its messages.
+/// The lines of a root source without a file are reported as part of [`ROOT_NAME`](Preprocessor::ROOT_NAME).
+/// ```rust,ignore
+/// let source = Preprocessor::new()
+///     .with_file("common.cl", "#pragma once\ntypedef float real;")
+///     .with_dir("kernels/include")
+///     .process("#include \"common.cl\"\n__kernel void f (__global real* x) {}")?;
+///
+/// let (program, kernels) = RawProgram::from_source(source, None)?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Preprocessor {
+    dirs: Vec<PathBuf>,
+    files: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Origin {
+    Virtual(String),
+    Disk(PathBuf),
+}
+
+#[derive(Default)]
+struct State {
+    stack: Vec<Origin>,
+    once: HashSet<Origin>,
+}
+
+impl Preprocessor {
+    /// Name of the root source in the `#line` directives of [`process`](Preprocessor::process).
+    pub const ROOT_NAME: &'static str = "<source>";
+
+    /// Creates a new preprocessor without include directories or virtual files.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a directory to search for included files.
+    #[inline]
+    pub fn with_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.dirs.push(path.into());
+        self
+    }
+
+    /// Adds an in-memory file, which can be included by `name`.
+    #[inline]
+    pub fn with_file(mut self, name: impl AsRef<str>, source: impl Into<String>) -> Self {
+        self.files.insert(normalize(name.as_ref()), source.into());
+        self
+    }
+
+    /// Resolves the includes of `source`.
+    #[inline]
+    pub fn process(&self, source: impl AsRef<str>) -> Result<String> {
+        let mut result = String::new();
+        self.expand(
+            None,
+            Self::ROOT_NAME,
+            source.as_ref(),
+            &mut State::default(),
+            &mut result,
+        )?;
+        return Ok(result);
+    }
+
+    /// Reads the file at `path` and resolves its includes. Its directory is searched first for the files it includes.
+    pub fn process_file(&self, path: impl AsRef<Path>) -> Result<String> {
+        let path = path.as_ref();
+        let source = read_file(path)?;
+        let origin = Origin::Disk(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+
+        let mut state = State::default();
+        state.stack.push(origin.clone());
+
+        let mut result = String::new();
+        let name = display_name(&origin);
+        self.expand(Some(&origin), &name, &source, &mut state, &mut result)?;
+        return Ok(result);
+    }
+
+    fn expand(
+        &self,
+        origin: Option<&Origin>,
+        name: &str,
+        source: &str,
+        state: &mut State,
+        result: &mut String,
+    ) -> Result<()> {
+        for (i, line) in source.lines().enumerate() {
+            let directive = line.trim_start().strip_prefix('#').map(str::trim_start);
+
+            if let Some(rest) = directive.and_then(|x| x.strip_prefix("pragma")) {
+                if rest.trim() == "once" {
+                    if let Some(origin) = origin {
+                        state.once.insert(origin.clone());
+                    }
+                    result.push('\n');
+                    continue;
+                }
+            }
+
+            let (include, angled) = match directive
+                .and_then(|x| x.strip_prefix("include"))
+                .and_then(parse_include)
+            {
+                Some(x) => x,
+                None => {
+                    result.push_str(line);
+                    result.push('\n');
+                    continue;
+                }
+            };
+
+            let (included, contents) = match self.resolve(origin, include, angled)? {
+                Some(x) => x,
+                // system headers may still be provided by the driver
+                None if angled => {
+                    result.push_str(line);
+                    result.push('\n');
+                    continue;
+                }
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::BuildProgramFailure,
+                        format!("included file `{include}` not found ({name}:{})", i + 1),
+                    ));
+                }
+            };
+
+            if state.once.contains(&included) {
+                result.push('\n');
+                continue;
+            }
+
+            if state.stack.contains(&included) {
+                return Err(Error::new(
+                    ErrorKind::BuildProgramFailure,
+                    format!("`{include}` is included recursively"),
+                ));
+            }
+
+            let included_name = display_name(&included);
+            state.stack.push(included.clone());
+            writeln!(result, "#line 1 \"{included_name}\"").unwrap();
+            self.expand(Some(&included), &included_name, &contents, state, result)?;
+            state.stack.pop();
+            writeln!(result, "#line {} \"{name}\"", i + 2).unwrap();
+        }
+
+        Ok(())
+    }
+
+    fn resolve(
+        &self,
+        parent: Option<&Origin>,
+        include: &str,
+        angled: bool,
+    ) -> Result<Option<(Origin, String)>> {
+        if !angled {
+            match parent {
+                Some(Origin::Disk(parent)) => {
+                    if let Some(path) = parent.parent().map(|x| x.join(include)) {
+                        if path.is_file() {
+                            return read_disk(path);
+                        }
+                    }
+                }
+
+                Some(Origin::Virtual(parent)) => {
+                    let name = match parent.rsplit_once('/') {
+                        Some((dir, _)) => normalize(&format!("{dir}/{include}")),
+                        None => normalize(include),
+                    };
+
+                    if let Some(source) = self.files.get(&name) {
+                        return Ok(Some((Origin::Virtual(name), source.clone())));
+                    }
+                }
+
+                None => {}
+            }
+        }
+
+        let name = normalize(include);
+        if let Some(source) = self.files.get(&name) {
+            return Ok(Some((Origin::Virtual(name), source.clone())));
+        }
+
+        for dir in self.dirs.iter() {
+            let path = dir.join(include);
+            if path.is_file() {
+                return read_disk(path);
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Parses the target of an `#include`, returning whether it's a system header (`<...>`).
+#[inline]
+fn parse_include(rest: &str) -> Option<(&str, bool)> {
+    let rest = rest.trim();
+    if let Some(rest) = rest.strip_prefix('"') {
+        return rest.split_once('"').map(|(x, _)| (x, false));
+    }
+    if let Some(rest) = rest.strip_prefix('<') {
+        return rest.split_once('>').map(|(x, _)| (x, true));
+    }
+    None
+}
+
+/// Normalizes the name of a virtual file, resolving `.` and `..`.
+fn normalize(name: &str) -> String {
+    let mut parts = Vec::new();
+    for part in name.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+#[inline]
+fn display_name(origin: &Origin) -> String {
+    match origin {
+        Origin::Virtual(name) => name.clone(),
+        Origin::Disk(path) => path.to_string_lossy().replace('\\', "/"),
+    }
+}
+
+#[inline]
+fn read_disk(path: PathBuf) -> Result<Option<(Origin, String)>> {
+    let source = read_file(&path)?;
+    let path = path.canonicalize().unwrap_or(path);
+    Ok(Some((Origin::Disk(path), source)))
+}
+
+#[inline]
+fn read_file(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|e| {
+        Error::new(
+            ErrorKind::BuildProgramFailure,
+            format!("error reading `{}`: {e}", path.display()),
+        )
+    })
+}
@@ -12,7 +12,6 @@ use opencl_sys::*;
 use std::{
     borrow::Cow,
     ffi::c_void,
-    ops::Deref,
     ptr::{addr_of_mut, NonNull},
 };
 
@@ -41,26 +40,30 @@ impl RawProgram {
         Self::from_il_in(Global::get(), source, options)
     }
 
+    #[docfg(feature = "cl2_2")]
+    #[inline(always)]
+    pub fn from_il_with_constants(
+        source: &[u8],
+        constants: &[SpecConstant<'_>],
+        options: Option<&str>,
+    ) -> Result<(Self, Box<[RawKernel]>)> {
+        Self::from_il_with_constants_in(Global::get(), source, constants, options)
+    }
+
     pub fn from_source_in<C: Context>(
         ctx: &C,
         source: impl AsRef<str>,
         options: Option<&str>,
     ) -> Result<(Self, Box<[RawKernel]>)> {
-        let options: Option<Cow<'static, str>> = match options {
-            Some(x) => {
-                let mut x = x.to_string();
-                #[cfg(feature = "cl1_2")]
-                x.push_str(" -cl-kernel-arg-info");
-                x.push('\0');
-                Some(Cow::Owned(x))
-            }
+        let this = Self::with_source_in(ctx, source)?;
+        this.build_in(ctx, options)?;
 
-            #[cfg(feature = "cl1_2")]
-            None => Some(Cow::Borrowed("-cl-kernel-arg-info\0")),
-            #[cfg(not(feature = "cl1_2"))]
-            None => None,
-        };
+        let kernels = this.create_kernels()?;
+        Ok((this, kernels))
+    }
 
+    /// Creates a program from source, without building it.
+    pub fn with_source_in<C: Context>(ctx: &C, source: impl AsRef<str>) -> Result<Self> {
         let source = source.as_ref();
         let len = [source.len()];
         let strings = [source.as_ptr().cast()];
@@ -80,27 +83,84 @@ impl RawProgram {
             return Err(Error::from(err));
         }
 
-        let this = NonNull::new(id).map(Self).unwrap();
-        this.build(options.as_deref(), ctx)?;
+        Ok(NonNull::new(id).map(Self).unwrap())
+    }
 
-        let kernels = this
+    /// Builds a program created from source for the devices of the context.
+    /// If the build fails, the description of the error are the program's [`BuildDiagnostics`].
+    pub fn build_in<C: Context>(&self, ctx: &C, options: Option<&str>) -> Result<()> {
+        let options: Option<Cow<'static, str>> = match options {
+            Some(x) => {
+                let mut x = x.to_string();
+                #[cfg(feature = "cl1_2")]
+                x.push_str(" -cl-kernel-arg-info");
+                x.push('\0');
+                Some(Cow::Owned(x))
+            }
+
+            #[cfg(feature = "cl1_2")]
+            None => Some(Cow::Borrowed("-cl-kernel-arg-info\0")),
+            #[cfg(not(feature = "cl1_2"))]
+            None => None,
+        };
+
+        self.build(&ctx.as_raw().devices()?, options.as_deref())
+    }
+
+    /// Creates a kernel object for every kernel of the built program.
+    #[inline]
+    pub fn create_kernels(&self) -> Result<Box<[RawKernel]>> {
+        let kernels = self
             .kernels()?
             .into_vec()
             .into_iter()
             .map(|id| unsafe { RawKernel::from_id(id).unwrap() })
             .collect::<Box<[_]>>();
-        Ok((this, kernels))
+        Ok(kernels)
     }
 
+    #[inline(always)]
     pub fn from_binary_in<C: Context>(ctx: &C, source: &[u8]) -> Result<(Self, Box<[RawKernel]>)> {
+        let devices = ctx.as_raw().num_devices()?;
+        let binaries = vec![source; devices as usize];
+        Self::from_binaries_in(ctx, &binaries)
+    }
+
+    /// Creates a program from a list of binaries, where each binary is loaded onto the context's device with the same index.
+    #[inline(always)]
+    pub fn from_binaries_in<C: Context>(
+        ctx: &C,
+        binaries: &[&[u8]],
+    ) -> Result<(Self, Box<[RawKernel]>)> {
+        Self::from_binaries_with_options_in(ctx, binaries, None)
+    }
+
+    /// Creates a program from a list of binaries, where each binary is loaded onto the context's device with the same index,
+    /// and builds it with the specified options (like [`build_in`](RawProgram::build_in)).
+    pub fn from_binaries_with_options_in<C: Context>(
+        ctx: &C,
+        binaries: &[&[u8]],
+        options: Option<&str>,
+    ) -> Result<(Self, Box<[RawKernel]>)> {
         let devices = ctx.as_raw().devices()?;
+        if binaries.len() != devices.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidValue,
+                format!(
+                    "expected {} binaries, found {}",
+                    devices.len(),
+                    binaries.len()
+                ),
+            ));
+        }
+
         let (num_devices, device_list) = (
             u32::try_from(devices.len()).unwrap(),
             devices.as_ptr().cast::<cl_device_id>(),
         );
 
-        let lengths = vec![source.len(); devices.len()];
-        let binaries = vec![source.as_ptr(); devices.len()];
+        let lengths = binaries.iter().map(|x| x.len()).collect::<Vec<_>>();
+        let binaries = binaries.iter().map(|x| x.as_ptr()).collect::<Vec<_>>();
 
         let mut binary_status = vec![CL_SUCCESS; devices.len()];
         let mut err = 0;
@@ -132,14 +192,9 @@ impl RawProgram {
         }
 
         let this = NonNull::new(id).map(Self).unwrap();
-        this.build(None, ctx)?;
+        this.build_in(ctx, options)?;
 
-        let kernels = this
-            .kernels()?
-            .into_vec()
-            .into_iter()
-            .map(|id| unsafe { RawKernel::from_id(id).unwrap() })
-            .collect::<Box<[_]>>();
+        let kernels = this.create_kernels()?;
         Ok((this, kernels))
     }
 
@@ -149,6 +204,35 @@ impl RawProgram {
         source: &[u8],
         options: Option<&str>,
     ) -> Result<(Self, Box<[RawKernel]>)> {
+        let this = Self::create_with_il(ctx, source)?;
+        this.build_il(ctx, options)
+    }
+
+    /// Creates a program from an intermediate language (e.g. SPIR-V) module, setting the specified specialization constants before building it.
+    #[docfg(feature = "cl2_2")]
+    pub fn from_il_with_constants_in<C: Context>(
+        ctx: &C,
+        source: &[u8],
+        constants: &[SpecConstant<'_>],
+        options: Option<&str>,
+    ) -> Result<(Self, Box<[RawKernel]>)> {
+        let this = Self::create_with_il(ctx, source)?;
+        for constant in constants {
+            unsafe {
+                tri!(opencl_sys::clSetProgramSpecializationConstant(
+                    this.id(),
+                    constant.id,
+                    constant.size,
+                    constant.value
+                ))
+            }
+        }
+
+        this.build_il(ctx, options)
+    }
+
+    #[cfg(feature = "cl2_1")]
+    fn create_with_il<C: Context>(ctx: &C, source: &[u8]) -> Result<Self> {
         let mut err = 0;
         let id = unsafe {
             clCreateProgramWithIL(
@@ -163,16 +247,24 @@ impl RawProgram {
             return Err(Error::from(err));
         }
 
-        let this = NonNull::new(id).map(Self).unwrap();
-        this.build(options.into(), ctx)?;
+        Ok(NonNull::new(id).map(Self).unwrap())
+    }
 
-        let kernels = this
-            .kernels()?
-            .into_vec()
-            .into_iter()
-            .map(|id| unsafe { RawKernel::from_id(id).unwrap() })
-            .collect::<Box<[_]>>();
-        Ok((this, kernels))
+    #[cfg(feature = "cl2_1")]
+    fn build_il<C: Context>(
+        self,
+        ctx: &C,
+        options: Option<&str>,
+    ) -> Result<(Self, Box<[RawKernel]>)> {
+        let options = options.map(|x| {
+            let mut x = x.to_string();
+            x.push('\0');
+            x
+        });
+        self.build(&ctx.as_raw().devices()?, options.as_deref())?;
+
+        let kernels = self.create_kernels()?;
+        Ok((self, kernels))
     }
 
     #[inline(always)]
@@ -301,42 +393,96 @@ impl RawProgram {
         self.get_info_string(CL_PROGRAM_SOURCE)
     }
 
+    /// Returns the log of the last build of the program for `device`.
+    pub fn build_log(&self, device: &RawDevice) -> Result<String> {
+        unsafe {
+            let mut len = 0;
+            tri!(clGetProgramBuildInfo(
+                self.id(),
+                device.id(),
+                CL_PROGRAM_BUILD_LOG,
+                0,
+                core::ptr::null_mut(),
+                &mut len
+            ));
+
+            if len <= 1 {
+                return Ok(String::new());
+            }
+
+            let mut result = Vec::<u8>::with_capacity(len);
+            tri!(clGetProgramBuildInfo(
+                self.id(),
+                device.id(),
+                CL_PROGRAM_BUILD_LOG,
+                len,
+                result.as_mut_ptr().cast(),
+                core::ptr::null_mut()
+            ));
+
+            result.set_len(len - 1);
+            Ok(String::from_utf8_lossy(&result).into_owned())
+        }
+    }
+
+    /// Returns the diagnostics of the last build of the program, like the warnings of a successful build.
+    #[inline(always)]
+    pub fn build_diagnostics(&self) -> Result<BuildDiagnostics> {
+        BuildDiagnostics::new(self)
+    }
+
     /// Returns an array that contains the size in bytes of the program binary for each device associated with program. The size of the array is the number of devices associated with program. If a binary is not available for a device(s), a size of zero is returned.
     #[inline]
     pub fn binary_sizes(&self) -> Result<Vec<Option<NonZeroUsize>>> {
-        let count = self.device_count()?;
-        let mut result = Vec::<Option<NonZeroUsize>>::with_capacity(count as usize);
-        let size = result
-            .capacity()
-            .checked_mul(core::mem::size_of::<usize>())
-            .expect("Too many binaries");
+        let sizes = self.get_info_array::<usize>(CL_PROGRAM_BINARY_SIZES)?;
+        Ok(sizes
+            .into_vec()
+            .into_iter()
+            .map(NonZeroUsize::new)
+            .collect())
+    }
+
+    /// Return the program binaries for all devices associated with program. The binaries are returned in the same order as the devices returned by [`devices`](RawProgram::devices). If a binary is not available for a device, [`None`] is returned in its place.
+    pub fn binaries(&self) -> Result<Vec<Option<Vec<u8>>>> {
+        let sizes = self.binary_sizes()?;
+        let mut result = sizes
+            .iter()
+            .map(|size| size.map(|x| Vec::<u8>::with_capacity(x.get())))
+            .collect::<Vec<_>>();
+
+        let mut ptrs = result
+            .iter_mut()
+            .map(|x| match x {
+                Some(x) => x.as_mut_ptr(),
+                None => core::ptr::null_mut(),
+            })
+            .collect::<Vec<_>>();
 
         unsafe {
             tri!(clGetProgramInfo(
                 self.id(),
-                CL_PROGRAM_DEVICES,
-                size,
-                result.as_mut_ptr().cast(),
+                CL_PROGRAM_BINARIES,
+                ptrs.len() * core::mem::size_of::<*mut u8>(),
+                ptrs.as_mut_ptr().cast(),
                 core::ptr::null_mut()
-            ))
+            ));
+
+            for (binary, size) in result.iter_mut().zip(sizes) {
+                if let (Some(binary), Some(size)) = (binary, size) {
+                    binary.set_len(size.get());
+                }
+            }
         }
 
-        unsafe { result.set_len(result.capacity()) }
         Ok(result)
     }
 
-    #[inline]
-    pub fn binaries(&self) -> Result<Vec<Option<Vec<u8>>>> {
-        todo!()
-    }
-
     #[allow(unused)]
     #[cfg(feature = "cl1_2")]
-    fn compile<C: Context>(
+    fn compile(
         &self,
         headers: Option<(&[&std::ffi::CStr], &[RawProgram])>,
         options: Option<&str>,
-        ctx: &C,
     ) -> Result<()> {
         let options = match options {
             Some(x) => x.as_ptr(),
@@ -374,10 +520,10 @@ impl RawProgram {
             )
         };
 
-        return self.build_error(build_result, ctx);
+        return self.build_error(build_result);
     }
 
-    fn build<C: Context>(&self, options: Option<&str>, ctx: &C) -> Result<()> {
+    fn build(&self, devices: &[RawDevice], options: Option<&str>) -> Result<()> {
         let ops = match options {
             Some(x) => x.as_ptr(),
             None => core::ptr::null(),
@@ -386,87 +532,29 @@ impl RawProgram {
         let build_result = unsafe {
             clBuildProgram(
                 self.id(),
-                0,
-                core::ptr::null(),
+                u32::try_from(devices.len()).unwrap(),
+                devices.as_ptr().cast(),
                 ops.cast(),
                 None,
                 core::ptr::null_mut(),
             )
         };
 
-        return self.build_error(build_result, ctx);
+        return self.build_error(build_result);
     }
 
-    fn build_error<C: Context>(&self, build_result: i32, ctx: &C) -> Result<()> {
+    fn build_error(&self, build_result: i32) -> Result<()> {
         if build_result == 0 {
             return Ok(());
         }
 
         let build_result = ErrorCode::from(build_result);
-
-        for device in ctx
-            .queues()
-            .into_iter()
-            .map(Deref::deref)
-            .map(RawCommandQueue::device)
-        {
-            let device = device?;
-
-            let mut len = 0;
-            unsafe {
-                tri!(clGetProgramBuildInfo(
-                    self.id(),
-                    device.id(),
-                    CL_PROGRAM_BUILD_LOG,
-                    0,
-                    core::ptr::null_mut(),
-                    &mut len
-                ))
-            };
-
-            if len <= 1 {
-                continue;
-            }
-
-            let mut result;
-            cfg_if::cfg_if! {
-                if #[cfg(feature = "nightly")] {
-                    result = Box::<[u8]>::new_uninit_slice(len);
-                } else {
-                    let mut vec = Vec::<MaybeUninit<u8>>::with_capacity(len);
-                    unsafe { vec.set_len(vec.capacity()) };
-                    result = vec.into_boxed_slice();
-                }
+        match BuildDiagnostics::new(self) {
+            Ok(diagnostics) if !diagnostics.is_empty() => {
+                Err(Error::new(build_result, diagnostics))
             }
-
-            unsafe {
-                tri!(clGetProgramBuildInfo(
-                    self.id(),
-                    device.id(),
-                    CL_PROGRAM_BUILD_LOG,
-                    len,
-                    result.as_mut_ptr().cast(),
-                    core::ptr::null_mut()
-                ))
-            };
-
-            let result = unsafe {
-                cfg_if::cfg_if! {
-                    if #[cfg(feature = "nightly")] {
-                        result.assume_init()
-                    } else {
-                        Box::from_raw(Box::into_raw(result) as *mut [u8])
-                    }
-                }
-            };
-
-            return Err(Error::new(
-                build_result,
-                String::from_utf8_lossy(&result[..result.len()]).into_owned(),
-            ));
+            _ => Err(build_result.into()),
         }
-
-        Err(build_result.into())
     }
 
     #[inline]
@@ -615,3 +703,38 @@ impl Drop for RawProgram {
 
 unsafe impl Send for RawProgram {}
 unsafe impl Sync for RawProgram {}
+
+/// Value of a specialization constant, to be set on an IL program before it's built.
+#[docfg(feature = "cl2_2")]
+#[derive(Debug, Clone, Copy)]
+pub struct SpecConstant<'a> {
+    id: u32,
+    size: usize,
+    value: *const c_void,
+    phtm: core::marker::PhantomData<&'a ()>,
+}
+
+#[cfg(feature = "cl2_2")]
+impl<'a> SpecConstant<'a> {
+    /// Creates a new specialization constant with the specified SPIR-V id.
+    #[inline(always)]
+    pub fn new<T: Copy>(id: u32, value: &'a T) -> Self {
+        Self {
+            id,
+            size: core::mem::size_of::<T>(),
+            value: (value as *const T).cast(),
+            phtm: core::marker::PhantomData,
+        }
+    }
+
+    /// Returns the SPIR-V id of the specialization constant.
+    #[inline(always)]
+    pub const fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+#[cfg(feature = "cl2_2")]
+unsafe impl Send for SpecConstant<'_> {}
+#[cfg(feature = "cl2_2")]
+unsafe impl Sync for SpecConstant<'_> {}
@@ -0,0 +1,188 @@
+use super::*;
+
+/// N-dimensional range of work-items over which a kernel is executed.
+/// ```rust,ignore
+/// // 1024x1024 work-items, in work-groups of 16x16, starting at (32, 32)
+/// let range = NdRange::new([1024, 1024]).with_local([16, 16]).with_offset([32, 32]);
+/// range.check(&device)?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NdRange<const N: usize> {
+    /// Offset of the global ID of the work-items.
+    pub offset: [usize; N],
+    /// Number of global work-items.
+    pub global: [usize; N],
+    /// Number of work-items in a work-group. If `None`, the OpenCL implementation will choose it.
+    pub local: Option<[usize; N]>,
+}
+
+impl<const N: usize> NdRange<N> {
+    /// Creates a new range with the specified global size, no offset and an implementation-defined local size.
+    #[inline(always)]
+    pub const fn new(global: [usize; N]) -> Self {
+        Self {
+            offset: [0; N],
+            global,
+            local: None,
+        }
+    }
+
+    /// Sets the offset of the range.
+    #[inline(always)]
+    pub const fn with_offset(self, offset: [usize; N]) -> Self {
+        Self { offset, ..self }
+    }
+
+    /// Sets the local size of the range.
+    #[inline(always)]
+    pub const fn with_local(self, local: [usize; N]) -> Self {
+        Self {
+            local: Some(local),
+            ..self
+        }
+    }
+
+    /// Total number of global work-items.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.global.iter().product()
+    }
+
+    /// Returns `true` if the range has no work-items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Rounds the global size up to a multiple of the local size, if any.
+    /// The kernel must check the bounds of its global IDs, since the new range may have work-items past the original one.
+    #[inline]
+    pub fn round_up(self) -> Self {
+        let local = match self.local {
+            Some(x) => x,
+            None => return self,
+        };
+
+        let mut global = self.global;
+        for (global, local) in global.iter_mut().zip(local) {
+            if local > 0 {
+                *global = global.div_ceil(local) * local;
+            }
+        }
+
+        return Self { global, ..self };
+    }
+
+    /// Checks that the range can be executed by `device`.
+    /// Returns an error if the range has more dimensions than the device supports, or if its local size exceeds the device's limits.
+    pub fn check(&self, device: &RawDevice) -> Result<()> {
+        let max_dims = device.max_work_item_dimensions()?.get() as usize;
+        if N == 0 || N > max_dims {
+            return Err(Error::new(
+                ErrorKind::InvalidWorkDimension,
+                format!(
+                    "ranges must have between 1 and {max_dims} dimensions, but this one has {N}"
+                ),
+            ));
+        }
+
+        let local = match self.local {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+
+        let max_sizes = device.max_work_item_sizes()?;
+        for (i, (local, max)) in local.into_iter().zip(max_sizes).enumerate() {
+            if local > max.get() {
+                return Err(Error::new(
+                    ErrorKind::InvalidWorkItemSize,
+                    format!("local size of dimension {i} is {local}, but the device supports up to {max}"),
+                ));
+            }
+        }
+
+        let size = local.iter().product::<usize>();
+        let max_size = device.max_work_group_size()?.get();
+        if size > max_size {
+            return Err(Error::new(
+                ErrorKind::InvalidWorkGroupSize,
+                format!(
+                    "work-groups have {size} work-items, but the device supports up to {max_size}"
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Splits the range into tiles of up to `tile` work-items, each with the offset of its sub-region.
+    /// For every tile to keep the local size of the range, `tile` must be a multiple of it, and the range must be [rounded up](NdRange::round_up).
+    /// # Panics
+    /// This method panics if any dimension of `tile` is zero.
+    #[inline]
+    pub fn tiles(self, tile: [usize; N]) -> Tiles<N> {
+        assert!(tile.iter().all(|x| *x > 0), "tiles cannot be empty");
+
+        let mut counts = [0; N];
+        for (count, (global, tile)) in counts.iter_mut().zip(self.global.iter().zip(tile)) {
+            *count = global.div_ceil(tile);
+        }
+
+        return Tiles {
+            range: self,
+            tile,
+            counts,
+            idx: 0,
+            len: counts.iter().product(),
+        };
+    }
+}
+
+impl<const N: usize> From<[usize; N]> for NdRange<N> {
+    #[inline(always)]
+    fn from(global: [usize; N]) -> Self {
+        Self::new(global)
+    }
+}
+
+/// Iterator over the tiles of an [`NdRange`], created by [`NdRange::tiles`].
+#[derive(Debug, Clone)]
+pub struct Tiles<const N: usize> {
+    range: NdRange<N>,
+    tile: [usize; N],
+    counts: [usize; N],
+    idx: usize,
+    len: usize,
+}
+
+impl<const N: usize> Iterator for Tiles<N> {
+    type Item = NdRange<N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.idx >= self.len {
+            return None;
+        }
+
+        // the first dimension varies the fastest
+        let mut idx = self.idx;
+        let mut result = self.range;
+        for i in 0..N {
+            let pos = (idx % self.counts[i]) * self.tile[i];
+            idx /= self.counts[i];
+
+            result.offset[i] += pos;
+            result.global[i] = usize::min(self.tile[i], self.range.global[i] - pos);
+        }
+
+        self.idx += 1;
+        return Some(result);
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len - self.idx;
+        (len, Some(len))
+    }
+}
+
+impl<const N: usize> ExactSizeIterator for Tiles<N> {}
@@ -1,8 +1,11 @@
 use std::{ptr::{NonNull, addr_of_mut}, ffi::c_void, mem::MaybeUninit};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use opencl_sys::*;
-use crate::prelude::{*};
+use crate::{prelude::{*}, non_null_const};
+#[cfg(all(feature = "cl2", not(feature = "strict")))]
+use super::device::Version;
 
+/// OpenCL sampler object, used by kernels to read images.
 #[repr(transparent)]
 pub struct Sampler (NonNull<c_void>);
 
@@ -12,7 +15,7 @@ impl Sampler {
         Self::new_in(&Global, props)
     }
 
-    #[cfg(not(featue = "cl2"))]
+    #[cfg(not(feature = "cl2"))]
     pub fn new_in (ctx: &RawContext, props: SamplerProperties) -> Result<Self> {
         let mut err = 0;
         let id;
@@ -25,7 +28,7 @@ impl Sampler {
         }
     }
 
-    #[cfg(featue = "cl2")]
+    #[cfg(feature = "cl2")]
     pub fn new_in (ctx: &RawContext, props: SamplerProperties) -> Result<Self> {
         let mut err = 0;
         let id;
@@ -53,7 +56,10 @@ impl Sampler {
 
     #[inline(always)]
     pub const unsafe fn from_id (id: cl_sampler) -> Option<Self> {
-        NonNull::new(id).map(Self)
+        match non_null_const(id) {
+            Some(x) => Some(Self(x)),
+            None => None
+        }
     }
 
     #[inline(always)]
@@ -77,7 +83,12 @@ impl Sampler {
     /// Return the context specified when the sampler is created.
     #[inline(always)]
     pub fn context (&self) -> Result<RawContext> {
-        self.get_info(CL_SAMPLER_CONTEXT)
+        let ctx = self.get_info::<cl_context>(CL_SAMPLER_CONTEXT)?;
+        unsafe {
+            tri!(clRetainContext(ctx));
+            // SAFETY: Context checked to be valid by `clRetainContext`.
+            Ok(RawContext::from_id_unchecked(ctx))
+        }
     }
 
     /// Return the normalized coords value associated with sampler.
@@ -123,6 +134,7 @@ impl Sampler {
         }
     }
 
+    #[cfg(feature = "cl3")]
     #[inline]
     fn get_info_array<T: Copy> (&self, ty: cl_sampler_info) -> Result<Box<[T]>> {
         let mut size = 0;
@@ -131,7 +143,16 @@ impl Sampler {
         }
 
         let len = size / core::mem::size_of::<T>();
-        let mut result = Box::<[T]>::new_uninit_slice(len);
+        let mut result;
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "nightly")] {
+                result = Box::<[T]>::new_uninit_slice(len);
+            } else {
+                let mut vec = Vec::<MaybeUninit<T>>::with_capacity(len);
+                unsafe { vec.set_len(vec.capacity()) };
+                result = vec.into_boxed_slice();
+            }
+        }
 
         unsafe {
             tri!(clGetSamplerInfo(self.id(), ty, size, result.as_mut_ptr().cast(), core::ptr::null_mut()));
@@ -199,7 +220,7 @@ impl SamplerProperties {
             }
         }
 
-        todo!()
+        result
     }
 
     #[inline(always)]
@@ -0,0 +1,190 @@
+use super::*;
+use crate::context::Context;
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    sync::{Mutex, MutexGuard},
+};
+
+/// Types that can be the type parameters of generic programs, like `#[blaze(Prog<T: ClType>)]`.
+///
+/// For every type parameter `T` of a generic program, its source is prepended with `typedef <type> T;` and the following defines:
+/// - `T_PRECISION`: number of bits of the type.
+/// - `T_IS_FLOAT`: `1` if the type is a floating-point number, `0` otherwise.
+/// - `T_IS_SIGNED`: `1` if the type is signed, `0` otherwise.
+///
+/// The extensions required by the type are also enabled.
+/// # Safety
+/// The constants must describe the OpenCL C type named [`CL_TYPE`](KernelArg::CL_TYPE).
+pub unsafe trait ClType: KernelArg + Send + Sync + 'static {
+    /// Number of bits of the type.
+    const PRECISION: u32 = (core::mem::size_of::<Self>() * 8) as u32;
+    /// `true` if the type is a floating-point number.
+    const IS_FLOAT: bool;
+    /// `true` if the type is signed.
+    const IS_SIGNED: bool;
+    /// OpenCL extensions required by the type, like `cl_khr_fp64`.
+    const EXTENSIONS: &'static [&'static str] = &[];
+}
+
+macro_rules! impl_cl_type {
+    ($($ty:ty => $float:literal, $signed:literal $(, $ext:literal)?);+) => {
+        $(
+            unsafe impl ClType for $ty {
+                const IS_FLOAT: bool = $float;
+                const IS_SIGNED: bool = $signed;
+                const EXTENSIONS: &'static [&'static str] = &[$($ext)?];
+            }
+        )+
+    };
+}
+
+impl_cl_type! {
+    i8 => false, true;
+    u8 => false, false;
+    i16 => false, true;
+    u16 => false, false;
+    i32 => false, true;
+    u32 => false, false;
+    i64 => false, true;
+    u64 => false, false;
+    f32 => true, true;
+    f64 => true, true, "cl_khr_fp64"
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "half")))]
+#[cfg(feature = "half")]
+unsafe impl ClType for ::half::f16 {
+    const IS_FLOAT: bool = true;
+    const IS_SIGNED: bool = true;
+    const EXTENSIONS: &'static [&'static str] = &["cl_khr_fp16"];
+}
+
+/// Type parameter of a generic program, instantiated with a [`ClType`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TypeParam {
+    pub name: &'static str,
+    pub cl_type: &'static str,
+    pub precision: u32,
+    pub is_float: bool,
+    pub is_signed: bool,
+    pub extensions: &'static [&'static str],
+}
+
+impl TypeParam {
+    /// Creates the type parameter `name`, instantiated with `T`.
+    #[inline(always)]
+    pub const fn of<T: ClType>(name: &'static str) -> Self {
+        Self {
+            name,
+            cl_type: T::CL_TYPE,
+            precision: T::PRECISION,
+            is_float: T::IS_FLOAT,
+            is_signed: T::IS_SIGNED,
+            extensions: T::EXTENSIONS,
+        }
+    }
+}
+
+/// Specializes the source of a generic program, prepending the definitions of its type parameters.
+/// The source is preceded by a `#line 1` directive, so the compiler reports the lines of the original source.
+pub fn specialize_source(params: &[TypeParam], source: &str) -> String {
+    let mut result = String::new();
+
+    let mut extensions = params.iter().flat_map(|x| x.extensions).collect::<Vec<_>>();
+    extensions.sort_unstable();
+    extensions.dedup();
+    for ext in extensions {
+        writeln!(result, "#pragma OPENCL EXTENSION {ext} : enable").unwrap();
+    }
+
+    for param in params {
+        let name = param.name;
+        writeln!(result, "typedef {} {name};", param.cl_type).unwrap();
+        writeln!(result, "#define {name}_PRECISION {}", param.precision).unwrap();
+        writeln!(result, "#define {name}_IS_FLOAT {}", param.is_float as u8).unwrap();
+        writeln!(result, "#define {name}_IS_SIGNED {}", param.is_signed as u8).unwrap();
+    }
+
+    result.push_str("#line 1\n");
+    result.push_str(source);
+    return result;
+}
+
+/// In-memory cache of the specializations of generic programs, held by their [context](Context::specializations).
+/// The cached programs are freed alongside the context.
+#[derive(Debug, Default)]
+pub struct Specializations {
+    programs: Mutex<HashMap<u64, RawProgram>>,
+}
+
+impl Specializations {
+    /// Creates a new, empty cache.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of cached specializations.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if no specializations are cached.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Removes every cached specialization, returning how many there were.
+    #[inline]
+    pub fn clear(&self) -> usize {
+        let mut programs = self.lock();
+        let len = programs.len();
+        programs.clear();
+        len
+    }
+
+    #[inline(always)]
+    fn lock(&self) -> MutexGuard<'_, HashMap<u64, RawProgram>> {
+        match self.programs.lock() {
+            Ok(x) => x,
+            Err(e) => e.into_inner(),
+        }
+    }
+}
+
+impl RawProgram {
+    /// Builds the specialization of a generic program's source for the specified type parameters.
+    ///
+    /// Specializations are built through the [global cache](ProgramCache::global), and are kept in memory by the context's [`Specializations`] (if it has them),
+    /// so a generic program is only built once per context and instantiation.
+    pub fn from_source_specialized_in<C: Context>(
+        ctx: &C,
+        params: &[TypeParam],
+        source: impl AsRef<str>,
+        options: Option<&str>,
+    ) -> Result<(Self, Box<[RawKernel]>)> {
+        let source = specialize_source(params, source.as_ref());
+        let specializations = match ctx.specializations() {
+            Some(x) => x,
+            None => return Self::from_source_cached_in(ctx, &source, options),
+        };
+
+        let mut hasher = Fnv1a::new();
+        hasher.write_str(&source);
+        hasher.write_str(options.unwrap_or_default());
+        let key = hasher.finish();
+
+        let cached = specializations.lock().get(&key).cloned();
+        if let Some(program) = cached {
+            let kernels = program.create_kernels()?;
+            return Ok((program, kernels));
+        }
+
+        let (program, kernels) = Self::from_source_cached_in(ctx, &source, options)?;
+        specializations.lock().insert(key, program.clone());
+        return Ok((program, kernels));
+    }
+}
@@ -0,0 +1,483 @@
+//! Auto-tuning of the local work-group size of kernel launches.
+//! Tuned kernels are executed once for every candidate, so they must be idempotent: running them several times must have the same effect as running them once.
+//! Since a kernel that isn't idempotent would corrupt its arguments, opting into auto-tuning with [`Auto::new`] is `unsafe`, even for safe kernels.
+//! ```rust,ignore
+//! #[blaze(Scale)]
+//! #[link = SCALE]
+//! extern "C" {
+//!     // `y[i] = a * x[i]`
+//!     fn scale(n: u32, a: f32, x: *const f32, y: *mut f32);
+//! }
+//!
+//! let scale = Scale::new(None)?;
+//! // the first launch benchmarks the candidate local sizes, the next ones reuse the winner
+//! unsafe { scale.scale_blocking(n, 2.0, &x, &mut y, [n as usize], Auto::new(), None)? };
+//! ```
+
+use super::*;
+use crate::WaitList;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Instant,
+};
+
+const MAGIC: &[u8; 4] = b"BLZT";
+const FORMAT_VERSION: u32 = 2;
+/// Number of times every candidate is executed. The fastest execution is kept.
+const ITERATIONS: usize = 3;
+
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+lazy_static! {
+    static ref GLOBAL_TUNER: Tuner = Tuner::from_env();
+}
+
+/// Local work-group size chosen by the [global tuner](Tuner::global).
+/// The kernel is executed once for every candidate local size, so it must be idempotent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Auto {
+    _priv: (),
+}
+
+impl Auto {
+    /// Opts into auto-tuning the local size of a kernel launch.
+    /// # Safety
+    /// The kernel must be idempotent: the first launch for every shape class executes it with its arguments once for every candidate local size,
+    /// so running it several times must have the same effect as running it once.
+    #[inline(always)]
+    pub const unsafe fn new() -> Self {
+        Self { _priv: () }
+    }
+}
+
+/// Local work-group size of a kernel launch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LocalSize<const N: usize> {
+    /// The local size of the [range](NdRange) is used. If it doesn't have one, the OpenCL implementation chooses it.
+    #[default]
+    Default,
+    Fixed([usize; N]),
+    /// The local size is chosen by the [global tuner](Tuner::global).
+    /// The first launch for every shape class executes the kernel once for every candidate local size, so the kernel must be idempotent (see [`Auto::new`]).
+    Auto(Auto),
+}
+
+impl<const N: usize> LocalSize<N> {
+    /// Returns `range` with the local size applied, tuning it if needed.
+    /// If `key` is `None`, the [key](KernelKey) of the kernel is computed when needed.
+    /// # Safety
+    /// If the local size is [`Auto`](LocalSize::Auto) and no result is cached, the kernel is executed with its current arguments for every candidate.
+    pub unsafe fn resolve(
+        self,
+        kernel: &mut RawKernel,
+        key: Option<&KernelKey>,
+        queue: &RawCommandQueue,
+        range: NdRange<N>,
+        wait: WaitList,
+    ) -> Result<NdRange<N>> {
+        let local = match self {
+            LocalSize::Default => return Ok(range),
+            LocalSize::Fixed(local) => return Ok(range.with_local(local)),
+            LocalSize::Auto(_) => match key {
+                Some(key) => Tuner::global().tune(kernel, key, queue, &range, wait)?,
+                None => {
+                    let key = KernelKey::new(kernel)?;
+                    Tuner::global().tune(kernel, &key, queue, &range, wait)?
+                }
+            },
+        };
+
+        return match local {
+            Some(local) => Ok(range.with_local(local)),
+            None => Ok(range),
+        };
+    }
+}
+
+impl<const N: usize> From<[usize; N]> for LocalSize<N> {
+    #[inline(always)]
+    fn from(local: [usize; N]) -> Self {
+        Self::Fixed(local)
+    }
+}
+
+impl<const N: usize> From<Option<[usize; N]>> for LocalSize<N> {
+    #[inline(always)]
+    fn from(local: Option<[usize; N]>) -> Self {
+        match local {
+            Some(local) => Self::Fixed(local),
+            None => Self::Default,
+        }
+    }
+}
+
+impl<const N: usize> From<Auto> for LocalSize<N> {
+    #[inline(always)]
+    fn from(auto: Auto) -> Self {
+        Self::Auto(auto)
+    }
+}
+
+/// Identity of a kernel for the [`Tuner`], one hash for every device of its program.
+/// Every hash covers the kernel's name, the program binary for the device, and the device's name, driver and platform.
+///
+/// Computing the key queries the program binaries, so it should be computed once and reused, like [`KernelPool`] does.
+#[derive(Debug, Clone)]
+pub struct KernelKey {
+    devices: Box<[(RawDevice, u64)]>,
+}
+
+impl KernelKey {
+    /// Computes the key of the kernel.
+    pub fn new(kernel: &RawKernel) -> Result<Self> {
+        let name = kernel.name()?;
+        let program = kernel.program()?;
+        let devices = program.devices()?;
+        // programs without binaries (like the ones created from built-in kernels) fall back to their source
+        let binaries = program.binaries().unwrap_or_default();
+        let mut source = None::<String>;
+
+        let mut result = Vec::with_capacity(devices.len());
+        for (i, device) in devices.into_iter().enumerate() {
+            let platform = device.platform()?;
+            let mut hasher = Fnv1a::new();
+            hasher.write_u32(FORMAT_VERSION);
+            hasher.write_str(&device.name()?);
+            hasher.write_str(&device.driver_version_string()?);
+            hasher.write_str(&platform.name()?);
+            hasher.write_str(&platform.version()?);
+            hasher.write_str(&name);
+
+            match binaries.get(i) {
+                Some(Some(binary)) => hasher.write(binary),
+                _ => {
+                    if source.is_none() {
+                        source = Some(program.source().unwrap_or_default());
+                    }
+                    hasher.write_str(source.as_deref().unwrap_or_default())
+                }
+            }
+
+            result.push((device, hasher.finish()));
+        }
+
+        Ok(Self {
+            devices: result.into_boxed_slice(),
+        })
+    }
+
+    /// Returns the hash of the kernel for `device`, if the kernel's program was built for it.
+    #[inline]
+    pub fn get(&self, device: &RawDevice) -> Option<u64> {
+        self.devices
+            .iter()
+            .find_map(|(x, hash)| (x == device).then_some(*hash))
+    }
+}
+
+/// Benchmarks candidate local sizes for kernel launches, remembering the fastest one.
+///
+/// Results are cached per device, kernel and shape class. Global sizes share a shape class if every dimension has the same power-of-two magnitude and the same power-of-two factor,
+/// so that every cached local size divides the global size it's used with.
+/// Persistent tuners also store their results in a file, which is loaded when they are created.
+#[derive(Debug)]
+pub struct Tuner {
+    path: Option<PathBuf>,
+    results: Mutex<HashMap<u64, Option<Box<[usize]>>>>,
+}
+
+impl Tuner {
+    /// Environment variable used to make the global tuner persistent.
+    pub const ENV_VAR: &'static str = "BLAZE_TUNING_FILE";
+
+    /// Creates a new tuner that keeps its results in memory.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self {
+            path: None,
+            results: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Creates a new tuner that stores its results in the file at `path`, loading the ones already stored.
+    /// Failing to read or write the file is not an error, the results are kept in memory instead.
+    #[inline]
+    pub fn persistent(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let results = read_results(&path).unwrap_or_default();
+
+        Self {
+            path: Some(path),
+            results: Mutex::new(results),
+        }
+    }
+
+    /// Creates a new tuner that stores its results in the file specified by the `BLAZE_TUNING_FILE` environment variable, if any.
+    #[inline]
+    pub fn from_env() -> Self {
+        match std::env::var_os(Self::ENV_VAR) {
+            Some(path) if !path.is_empty() => Self::persistent(path),
+            _ => Self::new(),
+        }
+    }
+
+    /// Returns the global tuner, used by [`Auto`] local sizes.
+    /// By default, the global tuner is only persistent if the `BLAZE_TUNING_FILE` environment variable is set.
+    #[inline(always)]
+    pub fn global() -> &'static Tuner {
+        &GLOBAL_TUNER
+    }
+
+    /// Returns the path of the tuner's file, if it's persistent.
+    #[inline(always)]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Returns the best local size for executing the kernel over `range`, benchmarking the [candidates](Tuner::candidates) if no result is cached.
+    /// Returns `None` if no candidate could be executed, in which case the OpenCL implementation should choose the local size.
+    /// # Safety
+    /// If no result is cached, the kernel is executed with its current arguments once for every candidate, so it must be idempotent.
+    pub unsafe fn tune<const N: usize>(
+        &self,
+        kernel: &mut RawKernel,
+        key: &KernelKey,
+        queue: &RawCommandQueue,
+        range: &NdRange<N>,
+        wait: WaitList,
+    ) -> Result<Option<[usize; N]>> {
+        let device = queue.device()?;
+        let key = match key.get(&device) {
+            Some(key) => Self::key(key, range.global),
+            None => return Ok(None),
+        };
+        if let Some(result) = self.get(key) {
+            return Ok(result);
+        }
+
+        let mut best = None::<(u64, [usize; N])>;
+        for local in Self::candidates(kernel, &device, range.global)? {
+            if let Some(nanos) = benchmark(kernel, queue, &range.with_local(local), wait) {
+                if best.map_or(true, |(x, _)| nanos < x) {
+                    best = Some((nanos, local));
+                }
+            }
+        }
+
+        let best = best.map(|(_, local)| local);
+        self.insert(key, best.map(|x| x.into()));
+        return Ok(best);
+    }
+
+    /// Returns the local sizes that are benchmarked for the kernel.
+    /// Every candidate has power-of-two dimensions that divide the global size and fit the limits of the kernel and device,
+    /// and its total size is a multiple of the kernel's preferred work-group size multiple, if possible.
+    pub fn candidates<const N: usize>(
+        kernel: &RawKernel,
+        device: &RawDevice,
+        global: [usize; N],
+    ) -> Result<Vec<[usize; N]>> {
+        let max_size = usize::min(
+            kernel.work_group_size(device)?,
+            device.max_work_group_size()?.get(),
+        );
+        let max_items = device.max_work_item_sizes()?;
+
+        let mut result = vec![[1; N]];
+        for (i, global) in global.into_iter().enumerate() {
+            let max_item = max_items.get(i).map_or(1, |x| x.get());
+            let mut next = Vec::with_capacity(result.len());
+
+            for local in result {
+                let size = local.iter().product::<usize>();
+                let mut item = 1;
+                while item <= max_item && global % item == 0 && size * item <= max_size {
+                    let mut local = local;
+                    local[i] = item;
+                    next.push(local);
+                    item *= 2;
+                }
+            }
+
+            result = next;
+        }
+
+        let multiple = preferred_multiple(kernel, device)?;
+        let is_multiple = |x: &[usize; N]| x.iter().product::<usize>() % multiple == 0;
+        if result.iter().any(is_multiple) {
+            result.retain(is_multiple);
+        }
+
+        return Ok(result);
+    }
+
+    /// Returns the key of the results for the kernel with the specified [hash](KernelKey::get) with the shape class of `global`.
+    pub fn key<const N: usize>(kernel: u64, global: [usize; N]) -> u64 {
+        let mut hasher = Fnv1a::new();
+        hasher.write(&kernel.to_le_bytes());
+        hasher.write_u32(N as u32);
+        for x in global {
+            hasher.write_u32(x.next_power_of_two().trailing_zeros());
+            hasher.write_u32(x.trailing_zeros().min(16));
+        }
+
+        hasher.finish()
+    }
+
+    /// Removes every result of the tuner, and its file if it's persistent.
+    pub fn clear(&self) -> std::io::Result<()> {
+        self.lock().clear();
+        match self.path {
+            Some(ref path) => match std::fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            },
+            None => Ok(()),
+        }
+    }
+
+    #[inline]
+    fn get<const N: usize>(&self, key: u64) -> Option<Option<[usize; N]>> {
+        return match self.lock().get(&key)? {
+            Some(local) => Some(Some(<[usize; N]>::try_from(&local[..]).ok()?)),
+            None => Some(None),
+        };
+    }
+
+    fn insert(&self, key: u64, local: Option<Box<[usize]>>) {
+        let mut results = self.lock();
+        results.insert(key, local);
+
+        if let Some(ref path) = self.path {
+            let _ = write_results(path, &results);
+        }
+    }
+
+    #[inline]
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u64, Option<Box<[usize]>>>> {
+        match self.results.lock() {
+            Ok(x) => x,
+            Err(e) => e.into_inner(),
+        }
+    }
+}
+
+impl Default for Tuner {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the fastest execution time of the kernel in nanoseconds, or `None` if it couldn't be executed.
+/// Queues without profiling are timed on the host.
+unsafe fn benchmark<const N: usize>(
+    kernel: &mut RawKernel,
+    queue: &RawCommandQueue,
+    range: &NdRange<N>,
+    wait: WaitList,
+) -> Option<u64> {
+    let mut best = u64::MAX;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        let event = kernel
+            .enqueue_unchecked(queue, *range, LocalSize::Default, wait)
+            .ok()?;
+        event.join_by_ref().ok()?;
+
+        let nanos = match event.profiling_nanos() {
+            Ok(info) => info.end.saturating_sub(info.start),
+            Err(_) => u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX),
+        };
+        best = best.min(nanos);
+    }
+
+    Some(best)
+}
+
+#[inline]
+fn preferred_multiple(kernel: &RawKernel, device: &RawDevice) -> Result<usize> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "cl1_1")] {
+            Ok(kernel.preferred_work_group_size_multiple(device)?.max(1))
+        } else {
+            let _ = (kernel, device);
+            Ok(1)
+        }
+    }
+}
+
+fn read_results(path: &Path) -> Option<HashMap<u64, Option<Box<[usize]>>>> {
+    let mut file = File::open(path).ok()?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).ok()?;
+
+    let mut bytes = bytes.strip_prefix(MAGIC)?;
+    let mut take = |len: usize| -> Option<&[u8]> {
+        if bytes.len() < len {
+            return None;
+        }
+        let (result, rest) = bytes.split_at(len);
+        bytes = rest;
+        Some(result)
+    };
+
+    if u32::from_le_bytes(take(4)?.try_into().ok()?) != FORMAT_VERSION {
+        return None;
+    }
+
+    let count = u32::from_le_bytes(take(4)?.try_into().ok()?);
+    let mut result = HashMap::with_capacity(count as usize);
+    for _ in 0..count {
+        let key = u64::from_le_bytes(take(8)?.try_into().ok()?);
+        // zero dimensions means that no candidate could be executed
+        let dims = take(1)?[0];
+        let local = match dims {
+            0 => None,
+            _ => Some(
+                (0..dims)
+                    .map(|_| Some(u64::from_le_bytes(take(8)?.try_into().ok()?) as usize))
+                    .collect::<Option<Box<[usize]>>>()?,
+            ),
+        };
+        result.insert(key, local);
+    }
+
+    Some(result)
+}
+
+fn write_results(path: &Path, results: &HashMap<u64, Option<Box<[usize]>>>) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // Write to a temporary file first, so that concurrent readers never see a partial file
+    let tmp = path.with_extension(format!(
+        "tmp{}-{}",
+        std::process::id(),
+        TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let mut file = File::create(&tmp)?;
+    file.write_all(MAGIC)?;
+    file.write_all(&FORMAT_VERSION.to_le_bytes())?;
+    file.write_all(&u32::try_from(results.len()).unwrap().to_le_bytes())?;
+    for (key, local) in results {
+        file.write_all(&key.to_le_bytes())?;
+        let local = local.as_deref().unwrap_or_default();
+        file.write_all(&[u8::try_from(local.len()).unwrap()])?;
+        for x in local {
+            file.write_all(&(*x as u64).to_le_bytes())?;
+        }
+    }
+    file.sync_all()?;
+    drop(file);
+
+    std::fs::rename(tmp, path)
+}
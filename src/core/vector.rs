@@ -0,0 +1,251 @@
+//! Host counterparts of the OpenCL C vector types, like `float4` or `int2`.
+//! Vectors have the same size and alignment as on the device, which is the size of the vector (`3`-component vectors have the size and alignment of `4`-component ones).
+//! ```rust
+//! use blaze_rs::core::vector::{Vec3, Vec4};
+//!
+//! assert_eq!(core::mem::size_of::<Vec3<f32>>(), 16);
+//! assert_eq!(core::mem::align_of::<Vec4<f32>>(), 16);
+//! assert_eq!(Vec4::new(1, 2, 3, 4) + Vec4::splat(1), Vec4::new(2, 3, 4, 5));
+//! ```
+
+use super::KernelArg;
+use blaze_proc::{NumOps, NumOpsAssign};
+use bytemuck::{Pod, Zeroable};
+use std::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+};
+
+/// Scalar types that can be the components of an OpenCL vector.
+/// # Safety
+/// Every alignment type must be a zero-sized type with the alignment of the vectors with the respective number of components.
+pub unsafe trait VectorScalar: KernelArg + Pod + Default {
+    #[doc(hidden)]
+    type Align2: Copy + Default + 'static;
+    #[doc(hidden)]
+    type Align4: Copy + Default + 'static;
+    #[doc(hidden)]
+    type Align8: Copy + Default + 'static;
+    #[doc(hidden)]
+    type Align16: Copy + Default + 'static;
+
+    /// OpenCL C names of the `2`, `3`, `4`, `8` and `16`-component vectors.
+    const CL_VECTORS: [&'static str; 5];
+}
+
+macro_rules! impl_align {
+    ($($name:ident => $align:literal),+) => {
+        $(
+            #[doc(hidden)]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+            #[repr(align($align))]
+            pub struct $name;
+        )+
+    };
+}
+
+impl_align! {
+    Align2 => 2,
+    Align4 => 4,
+    Align8 => 8,
+    Align16 => 16,
+    Align32 => 32,
+    Align64 => 64,
+    Align128 => 128
+}
+
+macro_rules! impl_scalar {
+    ($($ty:ty => $cl:literal: $a2:ident, $a4:ident, $a8:ident, $a16:ident),+) => {
+        $(
+            unsafe impl VectorScalar for $ty {
+                type Align2 = $a2;
+                type Align4 = $a4;
+                type Align8 = $a8;
+                type Align16 = $a16;
+
+                const CL_VECTORS: [&'static str; 5] = [
+                    concat!($cl, "2"),
+                    concat!($cl, "3"),
+                    concat!($cl, "4"),
+                    concat!($cl, "8"),
+                    concat!($cl, "16")
+                ];
+            }
+        )+
+    };
+}
+
+impl_scalar! {
+    i8 => "char": Align2, Align4, Align8, Align16,
+    u8 => "uchar": Align2, Align4, Align8, Align16,
+    i16 => "short": Align4, Align8, Align16, Align32,
+    u16 => "ushort": Align4, Align8, Align16, Align32,
+    i32 => "int": Align8, Align16, Align32, Align64,
+    u32 => "uint": Align8, Align16, Align32, Align64,
+    f32 => "float": Align8, Align16, Align32, Align64,
+    i64 => "long": Align16, Align32, Align64, Align128,
+    u64 => "ulong": Align16, Align32, Align64, Align128,
+    f64 => "double": Align16, Align32, Align64, Align128
+}
+
+/// Vector of 2 components, like `float2`.
+#[derive(Clone, Copy, Default, NumOps, NumOpsAssign)]
+#[repr(C)]
+pub struct Vec2<T: VectorScalar> {
+    pub x: T,
+    pub y: T,
+    #[pad]
+    _align: T::Align2,
+}
+
+/// Vector of 3 components, like `float3`. It has the size and alignment of a [`Vec4`].
+#[derive(Clone, Copy, Default, NumOps, NumOpsAssign)]
+#[repr(C)]
+pub struct Vec3<T: VectorScalar> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+    #[pad]
+    _pad: T,
+    #[pad]
+    _align: T::Align4,
+}
+
+/// Vector of 4 components, like `float4`.
+#[derive(Clone, Copy, Default, NumOps, NumOpsAssign)]
+#[repr(C)]
+pub struct Vec4<T: VectorScalar> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+    pub w: T,
+    #[pad]
+    _align: T::Align4,
+}
+
+/// Vector of 8 components, like `float8`.
+#[derive(Clone, Copy, Default, NumOps, NumOpsAssign)]
+#[repr(C)]
+pub struct Vec8<T: VectorScalar> {
+    pub s0: T,
+    pub s1: T,
+    pub s2: T,
+    pub s3: T,
+    pub s4: T,
+    pub s5: T,
+    pub s6: T,
+    pub s7: T,
+    #[pad]
+    _align: T::Align8,
+}
+
+/// Vector of 16 components, like `float16`.
+#[derive(Clone, Copy, Default, NumOps, NumOpsAssign)]
+#[repr(C)]
+pub struct Vec16<T: VectorScalar> {
+    pub s0: T,
+    pub s1: T,
+    pub s2: T,
+    pub s3: T,
+    pub s4: T,
+    pub s5: T,
+    pub s6: T,
+    pub s7: T,
+    pub s8: T,
+    pub s9: T,
+    pub sa: T,
+    pub sb: T,
+    pub sc: T,
+    pub sd: T,
+    pub se: T,
+    pub sf: T,
+    #[pad]
+    _align: T::Align16,
+}
+
+macro_rules! impl_vector {
+    ($($name:ident: $n:literal => $idx:literal { $($field:ident),+ }),+) => {
+        $(
+            impl<T: VectorScalar> $name<T> {
+                /// Creates a new vector from its components.
+                #[inline]
+                #[allow(clippy::too_many_arguments)]
+                pub fn new($($field: T),+) -> Self {
+                    Self {
+                        $($field,)+
+                        ..Default::default()
+                    }
+                }
+
+                /// Creates a new vector with every component set to `v`.
+                #[inline]
+                pub fn splat(v: T) -> Self {
+                    Self {
+                        $($field: v,)+
+                        ..Default::default()
+                    }
+                }
+
+                /// Returns the components of the vector.
+                #[inline]
+                pub fn to_array(self) -> [T; $n] {
+                    [$(self.$field),+]
+                }
+            }
+
+            impl<T: VectorScalar> From<[T; $n]> for $name<T> {
+                #[inline]
+                fn from([$($field),+]: [T; $n]) -> Self {
+                    Self::new($($field),+)
+                }
+            }
+
+            impl<T: VectorScalar> From<$name<T>> for [T; $n] {
+                #[inline]
+                fn from(v: $name<T>) -> Self {
+                    v.to_array()
+                }
+            }
+
+            unsafe impl<T: VectorScalar> Zeroable for $name<T> {}
+            unsafe impl<T: VectorScalar> Pod for $name<T> {}
+
+            unsafe impl<T: VectorScalar> KernelArg for $name<T> {
+                const CL_TYPE: &'static str = T::CL_VECTORS[$idx];
+            }
+
+            // padding isn't compared, since the device may write anything to it
+            impl<T: VectorScalar + PartialEq> PartialEq for $name<T> {
+                #[inline]
+                fn eq(&self, other: &Self) -> bool {
+                    $(self.$field == other.$field)&&+
+                }
+            }
+
+            impl<T: VectorScalar + Eq> Eq for $name<T> {}
+
+            impl<T: VectorScalar + Hash> Hash for $name<T> {
+                #[inline]
+                fn hash<H: Hasher>(&self, state: &mut H) {
+                    $(self.$field.hash(state);)+
+                }
+            }
+
+            impl<T: VectorScalar + Debug> Debug for $name<T> {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.debug_struct(stringify!($name))
+                        $(.field(stringify!($field), &self.$field))+
+                        .finish()
+                }
+            }
+        )+
+    };
+}
+
+impl_vector! {
+    Vec2: 2 => 0 { x, y },
+    Vec3: 3 => 1 { x, y, z },
+    Vec4: 4 => 2 { x, y, z, w },
+    Vec8: 8 => 3 { s0, s1, s2, s3, s4, s5, s6, s7 },
+    Vec16: 16 => 4 { s0, s1, s2, s3, s4, s5, s6, s7, s8, s9, sa, sb, sc, sd, se, sf }
+}
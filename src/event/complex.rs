@@ -61,13 +61,13 @@ use super::consumer::*;
 /// An event with a consumer that will be executed on the completion of the former.
 ///
 /// When using OpenCL 1.0, the event will also contain a sender that will send the event's callbacks,
-/// (like [`on_complete`](Event::on_complete)) to a different thread to be executed acordingly.
+/// (like [`on_complete`](Event::on_complete)) to the listener thread, which polls the event's status.
 #[derive(Debug, Clone)]
 pub struct Event<C> {
     inner: RawEvent,
     consumer: C,
     #[cfg(not(feature = "cl1_1"))]
-    send: std::sync::Arc<utils_atomics::FillQueue<super::listener::EventCallback>>,
+    send: std::sync::Arc<super::listener::Listener>,
     #[cfg(feature = "cl1_1")]
     send: std::marker::PhantomData<
         std::sync::Arc<
@@ -481,6 +481,18 @@ impl<'a, C: 'a + Consumer> Event<C> {
         crate::event::EventWait::new(self)
     }
 
+    /// Returns a future that waits for the event to complete without blocking.
+    /// If the future is dropped before the event completes, it blocks the current thread until it does,
+    /// so borrows used by the event's command stay alive even if the future is cancelled.
+    #[inline(always)]
+    #[docfg(feature = "futures")]
+    pub fn join_async_guarded(self) -> Result<crate::event::EventWait<C>>
+    where
+        C: Unpin,
+    {
+        crate::event::EventWait::new_guarded(self)
+    }
+
     /// Returns an event that completes when all the events inside `iter` complete (or one of them fails).
     /// The new event will return it's parents results inside a [`Vec`], in the same order they were in the iterator.\
     /// Note that if the iterator is empty, this funtion will return an error.
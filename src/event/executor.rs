@@ -0,0 +1,157 @@
+use crossbeam::channel::{Sender, TrySendError};
+use std::{
+    num::NonZeroUsize,
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{Arc, RwLock},
+};
+
+/// A callback, ready to be executed.
+pub type Task = Box<dyn 'static + FnOnce() + Send>;
+
+lazy_static! {
+    static ref EXECUTOR: RwLock<Arc<dyn CallbackExecutor>> =
+        RwLock::new(Arc::new(ThreadPool::default()));
+}
+
+/// Executor in charge of running the callbacks of events, like [`on_complete`](super::Event::on_complete) or the consumers of [`then`](super::Event::then).
+///
+/// By default, callbacks are executed by a small [`ThreadPool`]. The executor can be changed with [`set_callback_executor`], for example to use tokio's blocking pool.
+/// ```rust,ignore
+/// let handle = tokio::runtime::Handle::current();
+/// set_callback_executor(move |task| {
+///     handle.spawn_blocking(task);
+/// });
+/// ```
+pub trait CallbackExecutor: Send + Sync {
+    /// Executes the task, either on the current thread or on a different one.
+    fn execute(&self, task: Task);
+}
+
+impl<F: Fn(Task) + Send + Sync> CallbackExecutor for F {
+    #[inline(always)]
+    fn execute(&self, task: Task) {
+        self(task)
+    }
+}
+
+impl<T: ?Sized + CallbackExecutor> CallbackExecutor for Arc<T> {
+    #[inline(always)]
+    fn execute(&self, task: Task) {
+        T::execute(self, task)
+    }
+}
+
+/// Executes callbacks on the thread that reports the event's status change (usually a thread of the OpenCL driver).
+/// Callbacks should return promptly when using this executor.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Inline;
+
+impl CallbackExecutor for Inline {
+    #[inline(always)]
+    fn execute(&self, task: Task) {
+        task()
+    }
+}
+
+/// Fixed-size pool of threads with a bounded task queue.
+/// When the queue is full, tasks are executed on the calling thread instead.
+/// Panicking tasks don't bring down the threads of the pool.
+#[derive(Debug)]
+pub struct ThreadPool {
+    send: Sender<Task>,
+    threads: NonZeroUsize,
+}
+
+impl ThreadPool {
+    /// Creates a new pool with the specified number of threads, and a queue with space for `capacity` pending tasks.
+    pub fn new(threads: NonZeroUsize, capacity: usize) -> Self {
+        let (send, recv) = crossbeam::channel::bounded::<Task>(capacity);
+
+        for i in 0..threads.get() {
+            let recv = recv.clone();
+            std::thread::Builder::new()
+                .name(format!("Callback Handler {i}"))
+                .spawn(move || {
+                    while let Ok(task) = recv.recv() {
+                        let _ = catch_unwind(AssertUnwindSafe(task));
+                    }
+                })
+                .unwrap();
+        }
+
+        Self { send, threads }
+    }
+
+    /// Number of threads of the pool.
+    #[inline(always)]
+    pub fn threads(&self) -> NonZeroUsize {
+        self.threads
+    }
+}
+
+impl Default for ThreadPool {
+    /// Creates a pool with up to 4 threads (depending on the available parallelism) and space for 1024 pending tasks.
+    #[inline]
+    fn default() -> Self {
+        let threads = std::thread::available_parallelism()
+            .map_or(1, NonZeroUsize::get)
+            .min(4);
+
+        Self::new(NonZeroUsize::new(threads).unwrap(), 1024)
+    }
+}
+
+impl CallbackExecutor for ThreadPool {
+    #[inline]
+    fn execute(&self, task: Task) {
+        match self.send.try_send(task) {
+            Ok(_) => {}
+            Err(TrySendError::Full(task) | TrySendError::Disconnected(task)) => task(),
+        }
+    }
+}
+
+/// Sets the executor used to run event callbacks, returning the previous one.
+#[inline]
+pub fn set_callback_executor(
+    executor: impl 'static + CallbackExecutor,
+) -> Arc<dyn CallbackExecutor> {
+    let mut current = match EXECUTOR.write() {
+        Ok(x) => x,
+        Err(e) => e.into_inner(),
+    };
+
+    core::mem::replace(&mut current, Arc::new(executor))
+}
+
+/// Returns the executor used to run event callbacks.
+#[inline]
+pub fn callback_executor() -> Arc<dyn CallbackExecutor> {
+    match EXECUTOR.read() {
+        Ok(x) => x.clone(),
+        Err(e) => e.into_inner().clone(),
+    }
+}
+
+/// Sends the callback to the current executor.
+/// In debug builds, panics inside the callback are resumed on the executor's thread. Otherwise, they're caught and reported.
+pub(crate) fn dispatch(f: impl 'static + FnOnce() + Send) {
+    callback_executor().execute(Box::new(move || {
+        if let Err(e) = catch_unwind(AssertUnwindSafe(f)) {
+            report_panic(e)
+        }
+    }))
+}
+
+fn report_panic(e: Box<dyn 'static + std::any::Any + Send>) {
+    #[cfg(debug_assertions)]
+    std::panic::resume_unwind(e);
+    #[cfg(not(debug_assertions))]
+    match e.downcast::<String>() {
+        Ok(x) => eprintln!("{x}"),
+        Err(e) => match e.downcast::<&'static str>() {
+            Ok(x) => eprintln!("{x}"),
+            Err(_) => eprintln!("Panic with non-string payload"),
+        },
+    }
+}
@@ -1,86 +1,97 @@
 use super::{EventStatus, RawEvent};
 use crate::prelude::*;
-use once_cell::unsync::OnceCell;
 use opencl_sys::*;
-use std::{ffi::c_void, panic::*, sync::Arc};
+use std::{ffi::c_void, sync::Arc, thread::Thread, time::Duration};
 use utils_atomics::FillQueue;
 
-thread_local! {
-    static LISTENER_THREAD_STATUS : OnceCell<Arc<FillQueue<EventCallback>>> = OnceCell::new();
+/// Maximum time the listener sleeps between status checks.
+const MAX_BACKOFF: Duration = Duration::from_millis(1);
+
+lazy_static! {
+    static ref LISTENER: Arc<Listener> = Listener::spawn();
 }
 
-pub(super) fn get_sender () -> Arc<FillQueue<EventCallback>> {
-    LISTENER_THREAD_STATUS.with(|lts| {
-        lts.get_or_init(|| {
-            let queue = Arc::new(FillQueue::new());
-            let recv = Arc::downgrade(&queue);
-
-            std::thread::Builder::new()
-                .name(String::from("Callback Handler"))
-                .spawn(move || {
-                    let mut callbacks = Vec::<EventCallback>::with_capacity(1);
-                    let mut recv = Some(recv);
-
-                    loop {
-                        if let Some(ref queue) = recv {
-                            match queue.upgrade() {
-                                Some(queue) => callbacks.extend(queue.chop()),
-                                None => recv = None
-                            }
-                        }
-
-                        if recv.is_none() && callbacks.is_empty() {
-                            break
-                        }
-
-                        let mut i = 0;
-                        while i < callbacks.len() {
-                            let callback = unsafe { callbacks.get_unchecked(i) };
-                            let status = match callback.evt.status() {
-                                Ok(s) if s <= callback.status => Ok(callback.status),
-                                e @ Err(_) => e,
-                                Ok(_) => {
-                                    i += 1;
-                                    continue;
-                                }
-                            };
-
-                            let callback = callbacks.swap_remove(i);
-                            let v = match callback.cb {
-                                Callback::Boxed(f) => {
-                                    catch_unwind(AssertUnwindSafe(|| f(callback.evt, status)))
-                                }
-                                Callback::Raw(f, user_data) => unsafe {
-                                    let status = match status {
-                                        Ok(x) => x as i32,
-                                        Err(e) => e.ty.as_i32(),
-                                    };
-
-                                    catch_unwind(AssertUnwindSafe(|| {
-                                        f(callback.evt.id(), status, user_data)
-                                    }))
-                                },
-                            };
-
-                            if let Err(e) = v {
-                                #[cfg(debug_assertions)]
-                                resume_unwind(e);
-                                #[cfg(not(debug_assertions))]
-                                match Box::<dyn 'static + std::any::Any + Send>::downcast::<String>(e) {
-                                    Ok(x) => eprintln!("{x}"),
-                                    Err(e) => match Box::<dyn 'static + std::any::Any + Send>::downcast::<&'static str>(e) {
-                                        Ok(x) => eprintln!("{x}"),
-                                        Err(e) => eprintln!("Panic with non-strig payload")
-                                    }
-                                }
+/// OpenCL 1.0 doesn't support event callbacks, so their status is polled by a single listener thread,
+/// which sends the callbacks to the [callback executor](super::CallbackExecutor) when they're ready.
+/// The thread parks itself while there aren't any pending callbacks.
+pub(super) struct Listener {
+    queue: FillQueue<EventCallback>,
+    thread: Thread,
+}
+
+impl Listener {
+    fn spawn() -> Arc<Self> {
+        let (send, recv) = std::sync::mpsc::sync_channel::<Arc<Listener>>(1);
+        let thread = std::thread::Builder::new()
+            .name(String::from("Callback Listener"))
+            .spawn(move || {
+                let listener = recv.recv().unwrap();
+                let mut callbacks = Vec::<EventCallback>::with_capacity(1);
+                let mut backoff = Duration::from_micros(10);
+
+                loop {
+                    callbacks.extend(listener.queue.chop());
+                    if callbacks.is_empty() {
+                        std::thread::park();
+                        continue;
+                    }
+
+                    let prev_len = callbacks.len();
+                    let mut i = 0;
+                    while i < callbacks.len() {
+                        let callback = &callbacks[i];
+                        let status = match callback.evt.status() {
+                            Ok(s) if s <= callback.status => Ok(callback.status),
+                            e @ Err(_) => e,
+                            Ok(_) => {
+                                i += 1;
+                                continue;
                             }
-                        }
+                        };
+
+                        callbacks.swap_remove(i).dispatch(status);
+                    }
+
+                    // Back off while no callback is ready
+                    if callbacks.len() == prev_len {
+                        std::thread::park_timeout(backoff);
+                        backoff = (backoff * 2).min(MAX_BACKOFF);
+                    } else {
+                        backoff = Duration::from_micros(10);
                     }
-                })
-                .unwrap();
-            return queue
-        }).clone()
-    })
+                }
+            })
+            .unwrap();
+
+        let listener = Arc::new(Self {
+            queue: FillQueue::new(),
+            thread: thread.thread().clone(),
+        });
+
+        send.send(listener.clone()).unwrap();
+        return listener;
+    }
+
+    /// Adds a new callback to the listener.
+    #[inline]
+    pub fn push(&self, callback: EventCallback) {
+        self.queue.push(callback);
+        self.thread.unpark();
+    }
+}
+
+impl std::fmt::Debug for Listener {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Listener")
+            .field("thread", &self.thread)
+            .finish_non_exhaustive()
+    }
+}
+
+#[inline(always)]
+pub(super) fn get_sender() -> Arc<Listener> {
+    LISTENER.clone()
 }
 
 pub(super) struct EventCallback {
@@ -89,6 +100,23 @@ pub(super) struct EventCallback {
     pub cb: Callback,
 }
 
+impl EventCallback {
+    fn dispatch(self, status: Result<EventStatus>) {
+        let Self { evt, cb, .. } = self;
+        super::dispatch(move || match cb {
+            Callback::Boxed(f) => f(evt, status),
+            Callback::Raw(f, user_data) => unsafe {
+                let status = match status {
+                    Ok(x) => x as i32,
+                    Err(e) => e.ty.as_i32(),
+                };
+
+                f(evt.id(), status, user_data)
+            },
+        })
+    }
+}
+
 pub(super) enum Callback {
     Boxed(Box<dyn FnOnce(RawEvent, Result<EventStatus>) + Send + Sync>),
     Raw(
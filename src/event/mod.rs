@@ -1,4 +1,4 @@
-flat_mod!(raw, complex, status, profiling, eventual);
+flat_mod!(raw, complex, status, profiling, eventual, executor);
 
 #[path = "consumer.rs"]
 mod _consumer;
@@ -47,5 +47,19 @@ pub enum CommandType {
     UnmapMemObject = CL_COMMAND_UNMAP_MEM_OBJECT,
     Marker = CL_COMMAND_MARKER,
     AcquireGLObjects = CL_COMMAND_ACQUIRE_GL_OBJECTS,
-    ReleaseGLObjects = CL_COMMAND_RELEASE_GL_OBJECTS
+    ReleaseGLObjects = CL_COMMAND_RELEASE_GL_OBJECTS,
+    ReadBufferRect = CL_COMMAND_READ_BUFFER_RECT,
+    WriteBufferRect = CL_COMMAND_WRITE_BUFFER_RECT,
+    CopyBufferRect = CL_COMMAND_COPY_BUFFER_RECT,
+    User = CL_COMMAND_USER,
+    Barrier = CL_COMMAND_BARRIER,
+    MigrateMemObjects = CL_COMMAND_MIGRATE_MEM_OBJECTS,
+    FillBuffer = CL_COMMAND_FILL_BUFFER,
+    FillImage = CL_COMMAND_FILL_IMAGE,
+    SvmFree = CL_COMMAND_SVM_FREE,
+    SvmMemcpy = CL_COMMAND_SVM_MEMCPY,
+    SvmMemfill = CL_COMMAND_SVM_MEMFILL,
+    SvmMap = CL_COMMAND_SVM_MAP,
+    SvmUnmap = CL_COMMAND_SVM_UNMAP,
+    SvmMigrateMem = CL_COMMAND_SVM_MIGRATE_MEM
 }
\ No newline at end of file
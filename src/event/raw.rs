@@ -416,7 +416,7 @@ pub(crate) unsafe extern "C" fn event_listener(
     );
     let event = RawEvent::from_id_unchecked(event);
     let status = EventStatus::try_from(event_command_status);
-    f.call_once((event, status))
+    super::dispatch(move || f.call_once((event, status)))
 }
 
 pub type CallbackHandle<T> = ScopedCallbackHandle<'static, T>;
@@ -1,4 +1,4 @@
-use super::{consumer::Consumer, Event};
+use super::{consumer::Consumer, Event, RawEvent};
 use crate::prelude::Result;
 use futures::{future::FusedFuture, Future, FutureExt};
 use opencl_sys::*;
@@ -11,12 +11,13 @@ use utils_atomics::{
     FillQueue,
 };
 
-/// Future for [`join_async`](super::Event::join_async).
+/// Future for [`join_async`](super::Event::join_async) and [`join_async_guarded`](super::Event::join_async_guarded).
 #[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
 #[derive(Debug, Clone)]
 pub struct EventWait<C> {
     inner: Option<Event<C>>,
     sub: AsyncSubscribe,
+    guard: Option<RawEvent>,
 }
 
 impl<C: Unpin + Consumer> EventWait<C> {
@@ -30,8 +31,26 @@ impl<C: Unpin + Consumer> EventWait<C> {
         return Ok(Self {
             inner: Some(inner),
             sub,
+            guard: None,
         });
     }
+
+    /// Creates a future that blocks the current thread until the event completes if it's dropped before completing.
+    /// This keeps the borrows used by the event's command alive until the device is done with them, even if the future is cancelled.
+    #[inline]
+    pub fn new_guarded(inner: Event<C>) -> Result<Self> {
+        let guard = inner.as_raw().clone();
+        match Self::new(inner) {
+            Ok(mut this) => {
+                this.guard = Some(guard);
+                Ok(this)
+            }
+            Err(e) => {
+                let _ = guard.join_by_ref();
+                Err(e)
+            }
+        }
+    }
 }
 
 impl<C: Unpin + Consumer> Future for EventWait<C> {
@@ -52,6 +71,15 @@ impl<C: Unpin + Consumer> Future for EventWait<C> {
     }
 }
 
+impl<C> Drop for EventWait<C> {
+    #[inline]
+    fn drop(&mut self) {
+        if let (Some(_), Some(guard)) = (&self.inner, &self.guard) {
+            let _ = guard.join_by_ref();
+        }
+    }
+}
+
 impl<C: Unpin + Consumer> FusedFuture for EventWait<C> {
     #[inline(always)]
     fn is_terminated(&self) -> bool {
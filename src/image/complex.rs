@@ -215,4 +215,11 @@ impl<P: RawPixel, C: Context> DynImage2D for Image2D<P, C> {
     }
 }
 
+unsafe impl<P: RawPixel, C: Context> crate::memobj::KernelImage for Image2D<P, C> {
+    #[inline(always)]
+    fn id_ref (&self) -> &cl_mem {
+        MemObject::id_ref(self)
+    }
+}
+
 impl<P: RawPixel, C: Context> Sealed for Image2D<P, C> {}
\ No newline at end of file
@@ -1,4 +1,5 @@
-flat_mod!(raw, flags, complex, sampler);
+flat_mod!(raw, flags, complex);
+pub use crate::core::{AddressingMode, FilterMode, Sampler, SamplerProperties};
 pub mod channel;
 pub mod events;
 mod encdec;
\ No newline at end of file
@@ -75,8 +75,11 @@ mod blaze_rs {
 
 pub mod prelude {
     pub use crate::buffer::rect::{RectBox2D, RectBuffer2D};
-    pub use crate::buffer::{flags::*, Buffer, RawBuffer};
-    pub use crate::context::{scope, Context, Global, RawContext, Scope, SimpleContext};
+    pub use crate::buffer::{flags::*, Buffer, BufferVec, RawBuffer};
+    pub use crate::context::{
+        scope, Context, Global, MultiContext, RawContext, Scope, SimpleContext,
+    };
+    pub use crate::core::tuning::Auto;
     pub use crate::core::*;
     pub use crate::event::{Event, RawEvent};
     pub use crate::macros::*;
@@ -87,6 +90,9 @@ pub mod prelude {
 #[doc(hidden)]
 pub extern crate once_cell;
 
+#[doc(hidden)]
+pub extern crate linkme;
+
 #[cfg(feature = "futures")]
 #[doc(hidden)]
 pub extern crate futures;
@@ -103,6 +109,7 @@ pub mod macros {
     #[doc = include_str!("../docs/src/program/README.md")]
     pub use blaze_proc::blaze;
     pub use blaze_proc::global_context;
+    pub use blaze_proc::KernelArg;
 
     /// Similar to [`Event::join_all_blocking`](crate::event::Event::join_all_blocking), but it can also join events with different [`Consumer`](crate::event::Consumer)s
     /// ```rust
@@ -139,6 +146,8 @@ pub mod core;
 pub mod event;
 /// Generic memory object
 pub mod memobj;
+/// Kernel signature validation
+pub mod signature;
 pub(crate) mod thinfn;
 
 #[cfg_attr(docsrs, doc(cfg(feature = "image")))]
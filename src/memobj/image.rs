@@ -0,0 +1,34 @@
+use super::{MemObjectType, RawMemObject};
+use crate::core::*;
+use opencl_sys::cl_mem;
+use std::mem::ManuallyDrop;
+
+/// Memory objects that can be passed to kernels as images, like the `image2d` or `image3d` arguments of `#[blaze]` kernels.
+/// # Safety
+/// [`id_ref`](KernelImage::id_ref) must return a valid memory object.
+pub unsafe trait KernelImage: Sync {
+    fn id_ref(&self) -> &cl_mem;
+
+    /// Sets the image as the argument `idx` of the kernel, checking that it's an image of type `ty`.
+    unsafe fn set_arg(&self, kernel: &mut RawKernel, idx: u32, ty: MemObjectType) -> Result<()> {
+        let id = *self.id_ref();
+        let actual = ManuallyDrop::new(RawMemObject::from_id_unchecked(id)).ty()?;
+        if actual != ty {
+            return Err(Error::new(
+                ErrorKind::InvalidMemObject,
+                format!(
+                    "argument {idx} must be an image of type {ty:?}, but it's of type {actual:?}"
+                ),
+            ));
+        }
+
+        kernel.set_argument::<cl_mem, _>(idx, self.id_ref())
+    }
+}
+
+unsafe impl KernelImage for RawMemObject {
+    #[inline(always)]
+    fn id_ref(&self) -> &cl_mem {
+        RawMemObject::id_ref(self)
+    }
+}
@@ -1 +1 @@
-flat_mod!(raw, flags, region, utils, map);
\ No newline at end of file
+flat_mod!(raw, flags, region, utils, map, image);
\ No newline at end of file
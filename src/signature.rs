@@ -0,0 +1,647 @@
+//! Validation of the kernels declared with [`blaze`](crate::macros::blaze) against their OpenCL C source.
+//! Signatures are checked at compile time with [`check`], which is evaluated in const contexts so that mismatches are reported as compilation errors,
+//! and at runtime with [`RawKernel::validate_signature`](crate::core::RawKernel::validate_signature).
+
+use crate::core::*;
+use std::fmt::Display;
+
+/// Scalar type of a kernel argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scalar {
+    Char,
+    UChar,
+    Short,
+    UShort,
+    Int,
+    UInt,
+    Long,
+    ULong,
+    Half,
+    Float,
+    Double,
+    /// A type that isn't checked, like structs or typedefs.
+    Unknown,
+}
+
+impl Scalar {
+    /// Returns the scalar with the specified OpenCL C name, like `uint`.
+    #[inline]
+    pub const fn from_c_name(name: &str) -> Option<Self> {
+        match Element::from_c_name(name) {
+            Some(Element { scalar, width: 1 }) => Some(scalar),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the scalars are the same, or if either of them is [`Unknown`](Scalar::Unknown).
+    #[inline]
+    pub const fn is_compatible(self, other: Self) -> bool {
+        matches!(self, Scalar::Unknown)
+            || matches!(other, Scalar::Unknown)
+            || self as u8 == other as u8
+    }
+
+    /// Name of the scalar's Rust type.
+    #[inline]
+    pub const fn rust_name(self) -> &'static str {
+        match self {
+            Scalar::Char => "i8",
+            Scalar::UChar => "u8",
+            Scalar::Short => "i16",
+            Scalar::UShort => "u16",
+            Scalar::Int => "i32",
+            Scalar::UInt => "u32",
+            Scalar::Long => "i64",
+            Scalar::ULong => "u64",
+            Scalar::Half => "f16",
+            Scalar::Float => "f32",
+            Scalar::Double => "f64",
+            Scalar::Unknown => "_",
+        }
+    }
+}
+
+/// Type of a kernel argument (or of the pointee of a pointer argument): a scalar, or a [vector](crate::core::vector) of scalars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Element {
+    pub scalar: Scalar,
+    /// Number of components. Scalars have a single one.
+    pub width: u8,
+}
+
+impl Element {
+    #[inline]
+    pub const fn new(scalar: Scalar, width: u8) -> Self {
+        Self { scalar, width }
+    }
+
+    /// Returns the element with the specified OpenCL C name, like `uint` or `float4`.
+    #[inline]
+    pub const fn from_c_name(name: &str) -> Option<Self> {
+        let name = name.as_bytes();
+        element(name, 0, name.len())
+    }
+
+    /// Returns `true` if the elements are the same, or if the scalar of either of them is [`Unknown`](Scalar::Unknown).
+    #[inline]
+    pub const fn is_compatible(self, other: Self) -> bool {
+        matches!(self.scalar, Scalar::Unknown)
+            || matches!(other.scalar, Scalar::Unknown)
+            || (self.scalar as u8 == other.scalar as u8 && self.width == other.width)
+    }
+}
+
+impl From<Scalar> for Element {
+    #[inline]
+    fn from(scalar: Scalar) -> Self {
+        Self::new(scalar, 1)
+    }
+}
+
+impl Display for Element {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.width {
+            1 => f.write_str(self.scalar.rust_name()),
+            width => write!(f, "Vec{width}<{}>", self.scalar.rust_name()),
+        }
+    }
+}
+
+/// Kernel argument, as declared in Rust.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Arg {
+    Value(Element),
+    Pointer {
+        mutable: bool,
+        ty: Element,
+    },
+    Local(Element),
+    /// An argument whose declaration isn't checked, like images.
+    Other,
+}
+
+impl Display for Arg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Arg::Value(ty) => write!(f, "{ty}"),
+            Arg::Pointer { mutable: true, ty } => write!(f, "*mut {ty}"),
+            Arg::Pointer { mutable: false, ty } => write!(f, "*const {ty}"),
+            Arg::Local(ty) => write!(f, "local<[{ty}]>"),
+            Arg::Other => f.write_str("_"),
+        }
+    }
+}
+
+/// Result of [`check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mismatch {
+    None,
+    MissingKernel,
+    ArgCount,
+    Arg(usize, ArgMismatch),
+}
+
+/// Difference between a Rust argument and its OpenCL C declaration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArgMismatch {
+    Type,
+    Pointer,
+    AddressSpace,
+    Const,
+}
+
+#[derive(Clone, Copy)]
+enum Space {
+    Private,
+    Global,
+    Constant,
+    Local,
+}
+
+#[derive(Clone, Copy)]
+struct Param {
+    space: Space,
+    is_const: bool,
+    pointer: bool,
+    ty: Element,
+}
+
+/// Compares the signature of `kernel` in the OpenCL C source with the arguments declared in Rust.
+/// Macros aren't expanded, so a kernel that isn't found is assumed to be declared by a macro if the source has `#define` directives and uses its name (like `KERNEL(name)`).
+pub const fn check(source: &str, kernel: &str, args: &[Arg]) -> Mismatch {
+    let src = source.as_bytes();
+    let mut i = match find_kernel(src, kernel.as_bytes()) {
+        Some(i) => i,
+        None if declared_by_macro(src, kernel.as_bytes()) => return Mismatch::None,
+        None => return Mismatch::MissingKernel,
+    };
+
+    let mut idx = 0;
+    loop {
+        let (param, next, last) = parse_param(src, i);
+        i = next;
+
+        if let Some(param) = param {
+            if idx >= args.len() {
+                return Mismatch::ArgCount;
+            }
+
+            if let Some(mismatch) = check_arg(args[idx], param) {
+                return Mismatch::Arg(idx, mismatch);
+            }
+
+            idx += 1;
+        }
+
+        if last {
+            break;
+        }
+    }
+
+    match idx == args.len() {
+        true => Mismatch::None,
+        false => Mismatch::ArgCount,
+    }
+}
+
+const fn check_arg(arg: Arg, param: Param) -> Option<ArgMismatch> {
+    match arg {
+        Arg::Value(ty) => {
+            if param.pointer {
+                return Some(ArgMismatch::Pointer);
+            }
+            if !ty.is_compatible(param.ty) {
+                return Some(ArgMismatch::Type);
+            }
+        }
+
+        Arg::Pointer { mutable, ty } => {
+            if !param.pointer {
+                return Some(ArgMismatch::Pointer);
+            }
+            if !matches!(param.space, Space::Global | Space::Constant) {
+                return Some(ArgMismatch::AddressSpace);
+            }
+            if mutable == param.is_const {
+                return Some(ArgMismatch::Const);
+            }
+            if !ty.is_compatible(param.ty) {
+                return Some(ArgMismatch::Type);
+            }
+        }
+
+        Arg::Local(ty) => {
+            if !param.pointer {
+                return Some(ArgMismatch::Pointer);
+            }
+            if !matches!(param.space, Space::Local) {
+                return Some(ArgMismatch::AddressSpace);
+            }
+            if !ty.is_compatible(param.ty) {
+                return Some(ArgMismatch::Type);
+            }
+        }
+
+        Arg::Other => {}
+    }
+
+    None
+}
+
+/// Validates the kernel's signature with [`RawKernel::validate_signature`] if the `strict` feature is enabled.
+/// Kernels without argument information (like the ones built from binaries) aren't validated.
+#[doc(hidden)]
+#[inline]
+pub fn validate_strict(kernel: &RawKernel, args: &[Arg]) -> Result<()> {
+    cfg_if::cfg_if! {
+        if #[cfg(all(feature = "strict", feature = "cl1_2"))] {
+            match kernel.validate_signature(args) {
+                Err(e) if e.ty == ErrorCode::Kind(ErrorKind::KernelArgInfoNotAvailable) => Ok(()),
+                other => other,
+            }
+        } else {
+            let _ = (kernel, args);
+            Ok(())
+        }
+    }
+}
+
+/// Returns the position right after the opening parenthesis of the kernel's parameters.
+const fn find_kernel(src: &[u8], name: &[u8]) -> Option<usize> {
+    let mut i = 0;
+    let mut depth = 0usize;
+
+    'outer: loop {
+        let (s, e) = next_token(src, i);
+        if s == e {
+            return None;
+        }
+        i = e;
+
+        match src[s] {
+            b'{' => depth += 1,
+            b'}' => depth = depth.saturating_sub(1),
+            _ if depth == 0 && (eq(src, s, e, b"__kernel") || eq(src, s, e, b"kernel")) => {
+                let mut last = (0, 0);
+                loop {
+                    let (s, e) = next_token(src, i);
+                    if s == e {
+                        return None;
+                    }
+
+                    if eq(src, s, e, b"__attribute__") {
+                        i = skip_parens(src, e);
+                        continue;
+                    }
+
+                    match src[s] {
+                        b'(' => {
+                            i = e;
+                            break;
+                        }
+                        // not a kernel declaration, let the outer loop handle the token
+                        b';' | b'{' | b'}' => continue 'outer,
+                        _ => {
+                            last = (s, e);
+                            i = e;
+                        }
+                    }
+                }
+
+                if eq(src, last.0, last.1, name) {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns `true` if the source has `#define` directives and uses `name` as an identifier.
+const fn declared_by_macro(src: &[u8], name: &[u8]) -> bool {
+    has_defines(src) && has_ident(src, name)
+}
+
+/// Returns `true` if the source has `#define` directives.
+const fn has_defines(src: &[u8]) -> bool {
+    let mut i = 0;
+    while i < src.len() {
+        // skip the indentation of the line
+        while i < src.len() && matches!(src[i], b' ' | b'\t') {
+            i += 1;
+        }
+
+        if i < src.len() && src[i] == b'#' {
+            i += 1;
+            while i < src.len() && matches!(src[i], b' ' | b'\t') {
+                i += 1;
+            }
+
+            let mut e = i;
+            while e < src.len() && is_ident(src[e]) {
+                e += 1;
+            }
+            if eq(src, i, e, b"define") {
+                return true;
+            }
+        }
+
+        while i < src.len() && src[i] != b'\n' {
+            i += 1;
+        }
+        i += 1;
+    }
+
+    false
+}
+
+/// Returns `true` if `name` appears as an identifier in the source.
+const fn has_ident(src: &[u8], name: &[u8]) -> bool {
+    let mut i = 0;
+    while i < src.len() {
+        if !is_ident(src[i]) {
+            i += 1;
+            continue;
+        }
+
+        let mut e = i;
+        while e < src.len() && is_ident(src[e]) {
+            e += 1;
+        }
+        if eq(src, i, e, name) {
+            return true;
+        }
+        i = e;
+    }
+
+    false
+}
+
+/// Parses a parameter, returning it (if any), the position after it, and whether it was the last one.
+const fn parse_param(src: &[u8], mut i: usize) -> (Option<Param>, usize, bool) {
+    let mut param = Param {
+        space: Space::Private,
+        is_const: false,
+        pointer: false,
+        ty: Element::new(Scalar::Unknown, 1),
+    };
+
+    let mut unsigned = false;
+    let mut base = None::<Element>;
+    let mut others = 0;
+    let mut void = false;
+    let mut depth = 0usize;
+
+    let last = loop {
+        let (s, e) = next_token(src, i);
+        if s == e {
+            break true;
+        }
+        i = e;
+
+        match src[s] {
+            b'(' | b'[' if depth > 0 => depth += 1,
+            b'[' => {
+                param.pointer = true;
+                depth += 1
+            }
+            b'(' => depth += 1,
+            b')' | b']' if depth > 0 => depth -= 1,
+            b')' => break true,
+            b',' if depth == 0 => break false,
+            _ if depth > 0 => {}
+            b'*' => param.pointer = true,
+            _ if eq(src, s, e, b"__attribute__") => i = skip_parens(src, e),
+            _ if eq(src, s, e, b"__global") || eq(src, s, e, b"global") => {
+                param.space = Space::Global
+            }
+            _ if eq(src, s, e, b"__constant") || eq(src, s, e, b"constant") => {
+                param.space = Space::Constant;
+                param.is_const = true;
+            }
+            _ if eq(src, s, e, b"__local") || eq(src, s, e, b"local") => param.space = Space::Local,
+            _ if eq(src, s, e, b"__private") || eq(src, s, e, b"private") => {
+                param.space = Space::Private
+            }
+            // only the constness of the pointee matters
+            _ if eq(src, s, e, b"const") || eq(src, s, e, b"__const") => {
+                if !param.pointer {
+                    param.is_const = true
+                }
+            }
+            _ if eq(src, s, e, b"unsigned") => unsigned = true,
+            _ if eq(src, s, e, b"void") => void = true,
+            _ if is_qualifier(src, s, e) => {}
+            _ => match element(src, s, e) {
+                Some(ty) => base = Some(ty),
+                None => others += 1,
+            },
+        }
+    };
+
+    // `()` and `(void)` declare no parameters
+    if base.is_none() && others == 0 && !param.pointer {
+        return (None, i, last);
+    }
+
+    param.ty = match base {
+        Some(ty) if unsigned => {
+            let scalar = match ty.scalar {
+                Scalar::Char => Scalar::UChar,
+                Scalar::Short => Scalar::UShort,
+                Scalar::Int => Scalar::UInt,
+                Scalar::Long => Scalar::ULong,
+                other => other,
+            };
+            Element::new(scalar, ty.width)
+        }
+        Some(ty) => ty,
+        None if unsigned && !void => Element::new(Scalar::UInt, 1),
+        None => Element::new(Scalar::Unknown, 1),
+    };
+
+    (Some(param), i, last)
+}
+
+/// Parses a scalar or vector type, like `float` or `float4`.
+const fn element(src: &[u8], s: usize, e: usize) -> Option<Element> {
+    const TYPES: &[(&[u8], Scalar)] = &[
+        (b"char", Scalar::Char),
+        (b"uchar", Scalar::UChar),
+        (b"short", Scalar::Short),
+        (b"ushort", Scalar::UShort),
+        (b"int", Scalar::Int),
+        (b"uint", Scalar::UInt),
+        (b"long", Scalar::Long),
+        (b"ulong", Scalar::ULong),
+        (b"half", Scalar::Half),
+        (b"float", Scalar::Float),
+        (b"double", Scalar::Double),
+    ];
+
+    let mut i = 0;
+    while i < TYPES.len() {
+        let name = TYPES[i].0;
+        if e - s >= name.len() && eq(src, s, s + name.len(), name) {
+            let width = match e - s - name.len() {
+                0 => Some(1),
+                1 => match src[e - 1] {
+                    b'2' => Some(2),
+                    b'3' => Some(3),
+                    b'4' => Some(4),
+                    b'8' => Some(8),
+                    _ => None,
+                },
+                2 if src[e - 2] == b'1' && src[e - 1] == b'6' => Some(16),
+                _ => None,
+            };
+
+            if let Some(width) = width {
+                return Some(Element::new(TYPES[i].1, width));
+            }
+        }
+        i += 1;
+    }
+
+    None
+}
+
+const fn is_qualifier(src: &[u8], s: usize, e: usize) -> bool {
+    const QUALIFIERS: &[&[u8]] = &[
+        b"signed",
+        b"restrict",
+        b"__restrict",
+        b"volatile",
+        b"struct",
+        b"union",
+        b"enum",
+        b"__read_only",
+        b"read_only",
+        b"__write_only",
+        b"write_only",
+        b"__read_write",
+        b"read_write",
+    ];
+
+    let mut i = 0;
+    while i < QUALIFIERS.len() {
+        if eq(src, s, e, QUALIFIERS[i]) {
+            return true;
+        }
+        i += 1;
+    }
+
+    false
+}
+
+/// Skips a parenthesized group (like the arguments of `__attribute__`), starting before its opening parenthesis.
+const fn skip_parens(src: &[u8], mut i: usize) -> usize {
+    let mut depth = 0usize;
+    loop {
+        let (s, e) = next_token(src, i);
+        if s == e {
+            return e;
+        }
+        i = e;
+
+        match src[s] {
+            b'(' => depth += 1,
+            b')' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ if depth == 0 => return s,
+            _ => {}
+        }
+    }
+}
+
+/// Returns the bounds of the next token, skipping whitespace, comments and preprocessor directives.
+/// Both bounds are equal at the end of the source.
+const fn next_token(src: &[u8], i: usize) -> (usize, usize) {
+    let s = skip_trivia(src, i);
+    if s >= src.len() {
+        return (src.len(), src.len());
+    }
+
+    let mut e = s + 1;
+    if is_ident(src[s]) {
+        while e < src.len() && is_ident(src[e]) {
+            e += 1;
+        }
+    } else if src[s] == b'"' || src[s] == b'\'' {
+        while e < src.len() && src[e] != src[s] {
+            if src[e] == b'\\' {
+                e += 1;
+            }
+            e += 1;
+        }
+        e += 1;
+    }
+
+    (s, min(e, src.len()))
+}
+
+const fn skip_trivia(src: &[u8], mut i: usize) -> usize {
+    while i < src.len() {
+        match src[i] {
+            b' ' | b'\t' | b'\n' | b'\r' | 0x0b | 0x0c => i += 1,
+            b'/' if i + 1 < src.len() && src[i + 1] == b'/' => {
+                while i < src.len() && src[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if i + 1 < src.len() && src[i + 1] == b'*' => {
+                i += 2;
+                while i + 1 < src.len() && !(src[i] == b'*' && src[i + 1] == b'/') {
+                    i += 1;
+                }
+                i += 2;
+            }
+            b'#' => {
+                while i < src.len() && src[i] != b'\n' {
+                    if src[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            _ => break,
+        }
+    }
+
+    min(i, src.len())
+}
+
+#[inline]
+const fn is_ident(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+#[inline]
+const fn eq(src: &[u8], s: usize, e: usize, word: &[u8]) -> bool {
+    if e - s != word.len() {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < word.len() {
+        if src[s + i] != word[i] {
+            return false;
+        }
+        i += 1;
+    }
+
+    true
+}
+
+#[inline]
+const fn min(a: usize, b: usize) -> usize {
+    if a < b {
+        a
+    } else {
+        b
+    }
+}
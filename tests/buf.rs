@@ -50,6 +50,26 @@ fn write() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn map_mut() -> Result<()> {
+    let mut buf = buffer![1, 2, 3, 4, 5]?;
+    {
+        let mut map = buf.map_mut_blocking(1..4, None)?;
+        assert_eq!(&map as &[_], &[2, 3, 4]);
+        map[0] = 6;
+    }
+
+    scope(|s| {
+        let mut map = buf.map_mut(s, 3.., None)?.join()?;
+        assert_eq!(&map as &[_], &[4, 5]);
+        map[1] = 7;
+        Ok(())
+    })?;
+
+    assert_eq!(buf.read_blocking(.., None)?, vec![1, 6, 3, 4, 7]);
+    Ok(())
+}
+
 #[cfg(feature = "cl1_1")]
 #[test]
 fn slice() -> Result<()> {
@@ -109,6 +129,108 @@ fn chunks_mut() {
     assert_eq!(buf, buffer![1i32, 2, 1, 2, 5].unwrap());
 }
 
+#[cfg(feature = "cl1_1")]
+#[test]
+fn hazards() -> Result<()> {
+    use blaze_rs::{buffer::Access, event::FlagEvent};
+
+    let flag = FlagEvent::new()?;
+    let mut buf = buffer![1, 2, 3, 4, 5]?;
+    buf.track_hazards();
+    buf.hazard_tracker().unwrap().record(Access::Write, &flag);
+
+    let mut wait = Vec::new();
+    buf.hazard_tracker()
+        .unwrap()
+        .hazards(Access::Read, &mut wait);
+    assert!(wait.contains(&flag));
+
+    // slices inherit the hazards of their parent
+    let slice = buf.slice(..2)?;
+    let mut wait = Vec::new();
+    slice
+        .hazard_tracker()
+        .unwrap()
+        .hazards(Access::Write, &mut wait);
+    assert!(wait.contains(&flag));
+    drop(slice);
+
+    assert!(flag.try_mark(None)?);
+    assert_eq!(buf.read_blocking(.., None)?, vec![1, 2, 3, 4, 5]);
+    Ok(())
+}
+
+#[cfg(feature = "cl1_1")]
+#[test]
+fn hazards_order() -> Result<()> {
+    use blaze_rs::{
+        buffer::{Access, BufferRange},
+        context::ContextProperties,
+        event::FlagEvent,
+    };
+    use std::time::Duration;
+
+    let device = RawDevice::first().ok_or(ErrorKind::InvalidDevice)?;
+    let ctx = MultiContext::new(
+        core::slice::from_ref(device),
+        2,
+        ContextProperties::default(),
+        QueueProperties::default(),
+    )?;
+
+    let mut buf = Buffer::new_in(&ctx, &[1, 2, 3, 4, 5], MemAccess::default(), false)?;
+    buf.track_hazards();
+
+    // the write is held back by the flag, and the read is enqueued on the other queue
+    let flag = FlagEvent::new_in(&ctx)?;
+    let src = [6, 7, 8, 9, 10];
+    let write = unsafe {
+        let queue = ctx.next_queue();
+        buf.write_from_ptr_in(
+            BufferRange::from_parts::<i32>(0, src.len())?,
+            src.as_ptr().cast(),
+            queue,
+            Some(core::slice::from_ref(&flag)),
+        )?
+    };
+    buf.hazard_tracker().unwrap().record(Access::Write, &write);
+
+    let marker = {
+        let flag = flag.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            flag.try_mark(None)
+        })
+    };
+
+    assert_eq!(buf.read_blocking(.., None)?, vec![6, 7, 8, 9, 10]);
+    assert!(marker.join().unwrap()?);
+    Ok(())
+}
+
+#[test]
+fn vec() -> Result<()> {
+    let mut vec = BufferVec::<i32>::new(MemAccess::default());
+    assert_eq!(vec.capacity()?, 0);
+
+    vec.push_slice(&[1, 2, 3], None)?;
+    vec.push_slice(&[4, 5, 6], None)?;
+    assert!(vec.capacity()? >= 6);
+
+    vec.extend_from_buffer(&buffer![7, 8]?, None)?;
+    assert_eq!(vec.read_blocking(None)?, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+
+    vec.truncate(3);
+    vec.shrink_to_fit()?;
+    assert_eq!(vec.capacity()?, 3);
+    assert_eq!(vec.read_blocking(None)?, vec![1, 2, 3]);
+
+    vec.clear();
+    vec.shrink_to_fit()?;
+    assert_eq!(vec.capacity()?, 0);
+    Ok(())
+}
+
 /* RECT */
 cfg_if::cfg_if! {
     if #[cfg(feature = "cl1_1")] {
@@ -156,3 +278,47 @@ cfg_if::cfg_if! {
         }
     }
 }
+
+#[cfg(feature = "futures")]
+#[tokio::test]
+async fn futures() -> Result<()> {
+    let mut buf = buffer![1, 2, 3, 4, 5]?;
+    buf.write_async(1, vec![6, 7], None).await?;
+    assert_eq!(buf.read_async(.., None).await?, vec![1, 6, 7, 4, 5]);
+
+    let mut other = buffer![0; 5]?;
+    other.copy_from_async(2, &buf, 0, 3, None).await?;
+    assert_eq!(&other.map_async(.., None).await? as &[_], &[0, 0, 1, 6, 7]);
+
+    // futures can be awaited from other tasks
+    let read = tokio::spawn(async move { buf.read_async(3.., None).await });
+    assert_eq!(read.await.unwrap()?, vec![4, 5]);
+    Ok(())
+}
+
+#[cfg(all(feature = "futures", feature = "cl1_1"))]
+#[tokio::test]
+async fn futures_cancel() -> Result<()> {
+    use blaze_rs::event::FlagEvent;
+    use futures::FutureExt;
+    use std::time::Duration;
+
+    let mut buf = buffer![1, 2, 3]?;
+    let flag = FlagEvent::new()?;
+    let marker = {
+        let flag = flag.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            flag.try_mark(None)
+        })
+    };
+
+    // dropping a pending map waits for it to complete, and then unmaps the region
+    let map = buf.map_mut_async(.., Some(core::slice::from_ref(&flag)));
+    assert!(map.now_or_never().is_none());
+    assert!(marker.join().unwrap()?);
+
+    buf.write_blocking(0, &[4, 5, 6], None)?;
+    assert_eq!(buf.read_blocking(.., None)?, vec![4, 5, 6]);
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use blaze_rs::{
+    context::{ContextProperties, DeviceSelector, GlobalMarker, LeastLoaded, Profiler, Weighted},
+    event::CommandType,
+    prelude::*,
+};
+
+#[global_context(name = Compute)]
+static COMPUTE: SimpleContext = SimpleContext::default();
+
+#[test]
+fn multi() -> Result<()> {
+    let device = RawDevice::first().ok_or(ErrorKind::InvalidDevice)?;
+    let ctx = MultiContext::new(
+        core::slice::from_ref(device),
+        2,
+        ContextProperties::default(),
+        QueueProperties::default(),
+    )?;
+    assert_eq!(ctx.queues().len(), 2);
+
+    // round-robin alternates between queues
+    let first = ctx.next_queue().id();
+    assert_ne!(first, ctx.next_queue().id());
+    assert_eq!(first, ctx.next_queue().id());
+
+    let ctx = ctx.map_policy(|_| Ok(LeastLoaded))?;
+    assert_eq!(ctx.next_queue().id(), ctx.queues()[0].id());
+
+    // both queues share a device, so they have the same weight
+    let ctx = ctx.map_policy(Weighted::new)?;
+    let units = device.max_compute_units()?.get() as usize;
+    assert_eq!(queue_counts(&ctx, 2 * units), [units, units]);
+
+    let ctx = ctx.map_policy(|_| Ok(Weighted::from_weights([3, 1])))?;
+    assert_eq!(queue_counts(&ctx, 8), [6, 2]);
+    for queue in ctx.queues() {
+        queue.finish()?;
+    }
+
+    Ok(())
+}
+
+/// Counts how many times each queue is returned by `n` calls to `next_queue`
+fn queue_counts<C: Context>(ctx: &C, n: usize) -> Vec<usize> {
+    let mut counts = vec![0; ctx.queues().len()];
+    for _ in 0..n {
+        let id = ctx.next_queue().id();
+        let idx = ctx.queues().iter().position(|q| q.id() == id).unwrap();
+        counts[idx] += 1;
+    }
+    counts
+}
+
+#[test]
+fn select() -> Result<()> {
+    let candidates = DeviceSelector::new().ignore_env().candidates()?;
+    assert_eq!(candidates.len(), RawDevice::all().len());
+
+    let device = DeviceSelector::new()
+        .ignore_env()
+        .score(|dev| Ok(dev.max_compute_units()?.get() as i64))
+        .select()?;
+    assert!(RawDevice::all().contains(&device));
+
+    // no device has this much memory
+    let err = DeviceSelector::new()
+        .ignore_env()
+        .min_global_mem_size(u64::MAX)
+        .select()
+        .unwrap_err();
+    assert_eq!(err.ty, ErrorKind::DeviceNotFound.into());
+
+    Ok(())
+}
+
+#[test]
+fn profiler() -> Result<()> {
+    let device = RawDevice::first().ok_or(ErrorKind::InvalidDevice)?;
+    let ctx = MultiContext::new(
+        core::slice::from_ref(device),
+        1,
+        ContextProperties::default(),
+        CommandQueueProperties::new(false, true),
+    )?;
+
+    let profiler = Profiler::attach(&ctx);
+    let buf = Buffer::new_in(&ctx, &[1, 2, 3, 4, 5], MemAccess::default(), false)?;
+    assert_eq!(buf.read_blocking(.., None)?, vec![1, 2, 3, 4, 5]);
+
+    let records = profiler.records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].command, CommandType::ReadBuffer);
+
+    let mut trace = Vec::new();
+    profiler.write_chrome_trace(&mut trace).unwrap();
+    let trace = String::from_utf8(trace).unwrap();
+    assert!(trace.contains("\"ReadBuffer\""));
+
+    Profiler::detach(&ctx);
+    assert!(ctx.queues()[0].profiler().is_none());
+    Ok(())
+}
+
+#[test]
+fn named_global() -> Result<()> {
+    let compute = Global::<Compute>::new();
+    assert_eq!(compute.as_raw(), COMPUTE.as_raw());
+    assert!(std::ptr::eq(Compute::context(), &*COMPUTE));
+
+    let buf = Buffer::new_in(compute, &[1, 2, 3], MemAccess::default(), false)?;
+    assert_eq!(buf.read_blocking(.., None)?, vec![1, 2, 3]);
+    Ok(())
+}
+
+#[test]
+fn fallback_global() -> Result<()> {
+    // no default global context is declared, so one is created on first use
+    let buf = Buffer::new(&[1, 2, 3], MemAccess::default(), false)?;
+    assert_eq!(buf.read_blocking(.., None)?, vec![1, 2, 3]);
+    assert_eq!(Global.as_raw(), Global::get().as_raw());
+    assert_ne!(Global.as_raw(), COMPUTE.as_raw());
+    Ok(())
+}
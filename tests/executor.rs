@@ -0,0 +1,28 @@
+//! The callback executor is process-global, so this test has its own binary
+//! to keep the callbacks of other tests from running on its executor.
+#![cfg(feature = "cl1_1")]
+
+use blaze_rs::prelude::*;
+
+#[test]
+fn executor() -> Result<()> {
+    use blaze_rs::event::{set_callback_executor, FlagEvent, Task};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static SPAWNED: AtomicUsize = AtomicUsize::new(0);
+    let prev = set_callback_executor(|task: Task| {
+        SPAWNED.fetch_add(1, Ordering::Relaxed);
+        std::thread::spawn(task);
+    });
+
+    let flag = FlagEvent::new()?;
+    let handle = flag
+        .subscribe()
+        .on_complete(|_, _| panic!("callback panic"))?;
+    assert!(flag.try_mark(None)?);
+    assert!(handle.join().is_err());
+    assert!(SPAWNED.load(Ordering::Relaxed) >= 1);
+
+    set_callback_executor(prev);
+    Ok(())
+}
@@ -1,6 +1,21 @@
 #![allow(clippy::all)]
 
-use blaze_rs::prelude::{blaze, global_context, Result, SimpleContext};
+use blaze_rs::{
+    buffer,
+    context::{Context, ContextProperties, Global},
+    core::{
+        device::Version,
+        specialize_source,
+        tuning::{KernelKey, Tuner},
+        vector::{Vec3, Vec4},
+        BuildMessage, BuildOptions, KernelPool, LocalMem, NdRange, Preprocessor, ProgramCache,
+        QueueProperties, RawDevice, RawProgram, Sampler, SamplerProperties, Severity, TypeParam,
+    },
+    prelude::{
+        blaze, global_context, Auto, ClType, ErrorCode, ErrorKind, KernelArg, RawMemObject, Result,
+        SimpleContext,
+    },
+};
 use std::mem::MaybeUninit;
 
 #[global_context]
@@ -29,6 +44,187 @@ const KERNEL: &str = r#"
     }
     "#;
 
+#[blaze(Reduce)]
+#[link = REDUCE]
+extern "C" {
+    fn sum(n: u64, input: *const f32, output: *mut f32, scratch: local<[f32]>);
+}
+
+const REDUCE: &str = r#"
+    __kernel void sum (ulong n, const __global float* input, __global float* output, __local float* scratch) {
+        const size_t lid = get_local_id(0);
+        float acc = 0;
+        for (ulong i = get_global_id(0); i < n; i += get_global_size(0)) {
+            acc += input[i];
+        }
+
+        scratch[lid] = acc;
+        barrier(CLK_LOCAL_MEM_FENCE);
+        for (size_t s = get_local_size(0) / 2; s > 0; s >>= 1) {
+            if (lid < s) {
+                scratch[lid] += scratch[lid + s];
+            }
+            barrier(CLK_LOCAL_MEM_FENCE);
+        }
+
+        if (lid == 0) {
+            output[get_group_id(0)] = scratch[0];
+        }
+    }
+    "#;
+
+#[blaze(VecOps)]
+#[link = SCALE]
+extern "C" {
+    fn scale(n: u64, x: *mut Vec4<f32>, factor: Vec4<f32>);
+}
+
+const SCALE: &str = r#"
+    __kernel void scale (ulong n, __global float4* x, float4 factor) {
+        for (ulong i = get_global_id(0); i < n; i += get_global_size(0)) {
+            x[i] *= factor;
+        }
+    }
+    "#;
+
+#[blaze(Indices)]
+#[link = INDICES]
+extern "C" {
+    fn global_ids(width: u32, out: *mut u32);
+}
+
+const INDICES: &str = r#"
+    __kernel void global_ids (uint width, __global uint* out) {
+        const size_t x = get_global_id(0);
+        const size_t y = get_global_id(1);
+        out[y * width + x] = y * width + x;
+    }
+    "#;
+
+#[derive(Debug, Clone, Copy, PartialEq, KernelArg)]
+#[repr(C)]
+struct Particle {
+    position: [f32; 2],
+    mass: f32,
+}
+
+#[blaze(Particles)]
+#[link = particles_source()]
+extern "C" {
+    fn push(n: u64, particles: *mut Particle, force: Particle);
+}
+
+#[blaze(Blas<T: ClType>)]
+#[link = AXPY]
+extern "C" {
+    fn axpy(n: u64, alpha: T, x: *const T, y: *mut T);
+}
+
+const AXPY: &str = r#"
+__kernel void axpy (ulong n, T alpha, const __global T* x, __global T* y) {
+    for (ulong i = get_global_id(0); i < n; i += get_global_size(0)) {
+        y[i] += alpha * x[i];
+    }
+}
+"#;
+
+#[blaze(Volumes)]
+#[link = VOLUMES]
+extern "C" {
+    fn fill_volume(volume: image3d<write>, value: f32);
+    fn copy_layers(src: image2d_array, dst: image2d_array<write>);
+    fn sample_layers(src: image2d_array, sampler: sampler_t, dst: image2d_array<write>);
+}
+
+const VOLUMES: &str = r#"
+__kernel void fill_volume (write_only image3d_t volume, float value) {
+    int4 coord = (int4)(get_global_id(0), get_global_id(1), get_global_id(2), 0);
+    write_imagef(volume, coord, (float4)(value));
+}
+
+__kernel void copy_layers (read_only image2d_array_t src, write_only image2d_array_t dst) {
+    int4 coord = (int4)(get_global_id(0), get_global_id(1), get_global_id(2), 0);
+    write_imagef(dst, coord, read_imagef(src, coord));
+}
+
+__kernel void sample_layers (read_only image2d_array_t src, sampler_t sampler, write_only image2d_array_t dst) {
+    int4 coord = (int4)(get_global_id(0), get_global_id(1), get_global_id(2), 0);
+    write_imagef(dst, coord, read_imagef(src, sampler, coord));
+}
+"#;
+
+#[cfg(feature = "cl2")]
+#[blaze(Pipes)]
+#[link = PIPES]
+extern "C" {
+    fn produce(n: u32, out: pipe<write, u32>);
+    fn consume(input: pipe<u32>, out: *mut u32);
+}
+
+#[cfg(feature = "cl2")]
+const PIPES: &str = r#"
+__kernel void produce (uint n, write_only pipe uint out) {
+    uint value = get_global_id(0) * n;
+    write_pipe(out, &value);
+}
+
+__kernel void consume (read_only pipe uint input, __global uint* out) {
+    uint value;
+    if (read_pipe(input, &value) == 0) {
+        out[get_global_id(0)] = value;
+    }
+}
+"#;
+
+#[blaze(SafeBlas)]
+#[link = SAXPY]
+extern "C" {
+    #[extent(unsafe x = n, y = n)]
+    fn saxpy(n: u32, alpha: f32, x: *const f32, y: *mut f32);
+}
+
+const SAXPY: &str = r#"
+    __kernel void saxpy (uint n, float alpha, const __global float* x, __global float* y) {
+        for (uint i = get_global_id(0); i < n; i += get_global_size(0)) {
+            y[i] += alpha * x[i];
+        }
+    }
+    "#;
+
+#[cfg(feature = "cl2_1")]
+const FILL_IL: &[u8] = include_bytes!("spirv/fill.spv");
+
+#[cfg(feature = "cl2_1")]
+#[blaze(DefaultFill)]
+#[link_il = FILL_IL]
+extern "C" {
+    fn fill(out: *mut u32);
+}
+
+#[cfg(feature = "cl2_2")]
+#[blaze(SpecializedFill)]
+#[link_il = FILL_IL]
+#[spec_constants(0 = 42u32)]
+extern "C" {
+    #[link_name = "fill"]
+    fn fill_specialized(out: *mut u32);
+}
+
+fn particles_source() -> String {
+    format!(
+        "{}{}",
+        Particle::CL_DEFINITION,
+        r#"
+        __kernel void push (ulong n, __global Particle* particles, Particle force) {
+            for (ulong i = get_global_id(0); i < n; i += get_global_size(0)) {
+                particles[i].position[0] += force.position[0] / particles[i].mass;
+                particles[i].position[1] += force.position[1] / particles[i].mass;
+            }
+        }
+        "#
+    )
+}
+
 #[test]
 fn gemm() -> Result<()> {
     let tanh = FloatTanh::new(None)?;
@@ -36,3 +232,585 @@ fn gemm() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn cache() -> Result<()> {
+    let cache = ProgramCache::new(std::env::temp_dir().join("blaze-test-cache"));
+    let prev = ProgramCache::set_global(Some(cache.clone()));
+
+    let first = FloatTanh::new(None)?;
+    let second = FloatTanh::new(None)?;
+    assert_eq!(first.binaries()?, second.binaries()?);
+
+    // cached programs are rebuilt with their build options, so they keep their argument info
+    #[cfg(feature = "cl1_2")]
+    {
+        let (_, kernels) = cache.load_or_build_in(&Global, KERNEL, None)?;
+        assert!(kernels[0].arg_name(0).is_ok());
+    }
+
+    assert!(cache.invalidate_in(&Global, KERNEL, None).unwrap());
+    assert!(!cache.invalidate_in(&Global, KERNEL, None).unwrap());
+
+    ProgramCache::set_global(prev);
+    Ok(())
+}
+
+#[test]
+fn local() -> Result<()> {
+    let reduce = Reduce::new(None)?;
+    let input = buffer![1f32; 1000]?;
+    let mut output = buffer![0f32; 1]?;
+
+    let scratch = LocalMem::new(64);
+    unsafe { reduce.sum_blocking(1000, &input, &mut output, scratch, [64], [64], None)? };
+    assert_eq!(output.read_blocking(.., None)?, vec![1000f32]);
+
+    // empty local memory is rejected
+    let empty = LocalMem::new(0);
+    let res = unsafe { reduce.sum_blocking(1000, &input, &mut output, empty, [64], [64], None) };
+    assert!(res.is_err());
+
+    // so are sizes that overflow
+    let huge = LocalMem::<f32>::new(usize::MAX);
+    assert_eq!(huge.size(), None);
+    let res = unsafe { reduce.sum_blocking(1000, &input, &mut output, huge, [64], [64], None) };
+    assert!(res.is_err());
+    Ok(())
+}
+
+#[cfg(feature = "cl1_1")]
+#[test]
+fn sub_buffers() -> Result<()> {
+    let tanh = FloatTanh::new(None)?;
+    let mut buf = buffer![0.5f32; 1024]?;
+
+    // every chunk must start at an aligned offset
+    let align = buf.sub_buffer_align()?;
+    let len = (align / core::mem::size_of::<f32>()).max(1);
+    let n = u64::try_from(len).unwrap();
+
+    for chunk in buf.chunks_exact_mut(0, len) {
+        let mut chunk = chunk?;
+        unsafe { tanh.forward_blocking(n, &mut chunk, [len], None, None)? };
+    }
+
+    let expected = 0.5f32.tanh();
+    for (i, x) in buf.read_blocking(.., None)?.into_iter().enumerate() {
+        match i < 1024 - 1024 % len {
+            true => assert_eq!(x, expected),
+            false => assert_eq!(x, 0.5),
+        }
+    }
+
+    // misaligned sub-buffers are rejected
+    if align > core::mem::size_of::<f32>() {
+        assert!(buf.slice(1..).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn signature() {
+    use blaze_rs::signature::{check, Arg, ArgMismatch, Element, Mismatch, Scalar};
+
+    let forward = [
+        Arg::Value(Scalar::ULong.into()),
+        Arg::Pointer {
+            mutable: true,
+            ty: Scalar::Float.into(),
+        },
+    ];
+    assert_eq!(check(KERNEL, "forward", &forward), Mismatch::None);
+    assert_eq!(check(KERNEL, "sideways", &forward), Mismatch::MissingKernel);
+
+    // kernels may be declared by macros, which aren't expanded
+    const MACRO: &str = "#define KERNEL(name) __kernel void name\nKERNEL(sideways) (ulong n) {}";
+    assert_eq!(check(MACRO, "sideways", &forward), Mismatch::None);
+    // but only if the macro source uses the kernel's name
+    assert_eq!(check(MACRO, "backwards", &forward), Mismatch::MissingKernel);
+    const DEFINES: &str = "#define N 4\n__kernel void forward (ulong n) {}";
+    assert_eq!(
+        check(DEFINES, "sideways", &forward),
+        Mismatch::MissingKernel
+    );
+    assert_eq!(check(KERNEL, "forward", &forward[..1]), Mismatch::ArgCount);
+
+    // `x_buffer` is `const __global float*`
+    let backward = [
+        Arg::Value(Scalar::ULong.into()),
+        Arg::Pointer {
+            mutable: true,
+            ty: Scalar::Float.into(),
+        },
+        Arg::Pointer {
+            mutable: true,
+            ty: Scalar::Float.into(),
+        },
+    ];
+    assert_eq!(
+        check(KERNEL, "backward", &backward),
+        Mismatch::Arg(1, ArgMismatch::Const)
+    );
+
+    let sum = [
+        Arg::Value(Scalar::UInt.into()),
+        Arg::Pointer {
+            mutable: false,
+            ty: Scalar::Float.into(),
+        },
+        Arg::Pointer {
+            mutable: true,
+            ty: Scalar::Float.into(),
+        },
+        Arg::Local(Scalar::Float.into()),
+    ];
+    assert_eq!(
+        check(REDUCE, "sum", &sum),
+        Mismatch::Arg(0, ArgMismatch::Type)
+    );
+
+    // `factor` is a `float4`
+    let mut scale = [
+        Arg::Value(Scalar::ULong.into()),
+        Arg::Pointer {
+            mutable: true,
+            ty: Element::new(Scalar::Float, 4),
+        },
+        Arg::Value(Element::new(Scalar::Float, 4)),
+    ];
+    assert_eq!(check(SCALE, "scale", &scale), Mismatch::None);
+    scale[2] = Arg::Value(Scalar::Float.into());
+    assert_eq!(
+        check(SCALE, "scale", &scale),
+        Mismatch::Arg(2, ArgMismatch::Type)
+    );
+}
+
+#[cfg(feature = "cl1_2")]
+#[test]
+fn validate_signature() -> Result<()> {
+    use blaze_rs::{
+        core::RawProgram,
+        signature::{Arg, Scalar},
+    };
+
+    let (_, kernels) = RawProgram::from_source(KERNEL, None)?;
+    let forward = kernels
+        .iter()
+        .find(|x| x.name().unwrap() == "forward")
+        .unwrap();
+
+    let args = [
+        Arg::Value(Scalar::ULong.into()),
+        Arg::Pointer {
+            mutable: true,
+            ty: Scalar::Float.into(),
+        },
+    ];
+    forward.validate_signature(&args)?;
+
+    // `x_buffer` isn't `const`
+    let args = [
+        Arg::Value(Scalar::ULong.into()),
+        Arg::Pointer {
+            mutable: false,
+            ty: Scalar::Float.into(),
+        },
+    ];
+    assert!(forward.validate_signature(&args).is_err());
+    assert!(forward.validate_signature(&args[..1]).is_err());
+    Ok(())
+}
+
+#[test]
+fn kernel_arg() -> Result<()> {
+    assert_eq!(Particle::CL_TYPE, "Particle");
+    assert!(Particle::CL_DEFINITION
+        .starts_with("typedef struct {\n    float position[2];\n    float mass;\n} Particle;\n"));
+
+    let particles = Particles::new(None)?;
+    let particle = Particle {
+        position: [0.0, 1.0],
+        mass: 2.0,
+    };
+    let mut buf = buffer![particle; 4]?;
+
+    let force = Particle {
+        position: [1.0, 2.0],
+        mass: 0.0,
+    };
+    unsafe { particles.push_blocking(4, &mut buf, force, [4], None, None)? };
+
+    let expected = Particle {
+        position: [0.5, 2.0],
+        mass: 2.0,
+    };
+    assert_eq!(buf.read_blocking(.., None)?, vec![expected; 4]);
+    Ok(())
+}
+
+#[test]
+fn vector() -> Result<()> {
+    assert_eq!(core::mem::size_of::<Vec3<f32>>(), 16);
+    assert_eq!(core::mem::align_of::<Vec3<f32>>(), 16);
+    assert_eq!(core::mem::align_of::<Vec4<u8>>(), 4);
+    assert_eq!(Vec4::<f32>::CL_TYPE, "float4");
+    assert_eq!(Vec3::<i32>::CL_TYPE, "int3");
+
+    let x = Vec4::new(1f32, 2.0, 3.0, 4.0);
+    assert_eq!((x * Vec4::splat(2.0)).to_array(), [2.0, 4.0, 6.0, 8.0]);
+
+    let ops = VecOps::new(None)?;
+    let mut buf = buffer![x; 8]?;
+    unsafe { ops.scale_blocking(8, &mut buf, Vec4::splat(0.5), [8], None, None)? };
+
+    let expected = Vec4::new(0.5, 1.0, 1.5, 2.0);
+    assert_eq!(buf.read_blocking(.., None)?, vec![expected; 8]);
+    Ok(())
+}
+
+#[test]
+fn nd_range() -> Result<()> {
+    let range = NdRange::new([10, 6]).with_local([4, 4]).round_up();
+    assert_eq!(range.global, [12, 8]);
+
+    let tiles = NdRange::new([10, 6]).tiles([4, 4]).collect::<Vec<_>>();
+    assert_eq!(tiles.len(), 6);
+    assert_eq!(tiles[2].offset, [8, 0]);
+    assert_eq!(tiles[2].global, [2, 4]);
+    assert_eq!(tiles[5].offset, [8, 4]);
+    assert_eq!(tiles[5].global, [2, 2]);
+
+    let device = RawDevice::first().unwrap();
+    NdRange::new([64]).with_local([1]).check(device)?;
+    let max = device.max_work_group_size()?.get();
+    assert!(NdRange::new([max + 1])
+        .with_local([max + 1])
+        .check(device)
+        .is_err());
+
+    // every tile is launched with its own offset
+    let indices = Indices::new(None)?;
+    let mut buf = buffer![0u32; 60]?;
+    for tile in NdRange::new([10, 6]).tiles([4, 4]) {
+        unsafe { indices.global_ids_blocking(10, &mut buf, tile, None, None)? };
+    }
+
+    assert_eq!(buf.read_blocking(.., None)?, (0..60).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[test]
+fn tuning() -> Result<()> {
+    let indices = Indices::new(None)?;
+    let mut buf = buffer![0u32; 512]?;
+    for _ in 0..2 {
+        unsafe { indices.global_ids_blocking(64, &mut buf, [64, 8], Auto::new(), None)? };
+    }
+    assert_eq!(buf.read_blocking(.., None)?, (0..512).collect::<Vec<_>>());
+
+    let (_, kernels) = RawProgram::from_source(INDICES, None)?;
+    let mut kernel = kernels[0].clone();
+    let queue = Global.next_queue();
+    let device = queue.device()?;
+
+    let candidates = Tuner::candidates(&kernel, &device, [64, 8])?;
+    assert!(!candidates.is_empty());
+    for local in candidates {
+        assert_eq!(64 % local[0], 0);
+        assert_eq!(8 % local[1], 0);
+    }
+
+    // results are stored on disk
+    let path = std::env::temp_dir().join("blaze-test-tuning.bin");
+    let tuner = Tuner::persistent(&path);
+    tuner.clear().unwrap();
+
+    let range = NdRange::new([64, 8]);
+    unsafe {
+        kernel.set_argument(0, 64u32)?;
+        kernel.set_argument(1, *buf.id_ref())?;
+        let key = KernelKey::new(&kernel)?;
+        let local = tuner.tune(&mut kernel, &key, queue, &range, None)?;
+        assert!(local.is_some());
+        assert_eq!(
+            Tuner::persistent(&path).tune(&mut kernel, &key, queue, &range, None)?,
+            local
+        );
+    }
+
+    tuner.clear().unwrap();
+    Ok(())
+}
+
+#[test]
+fn diagnostics() -> Result<()> {
+    let message =
+        BuildMessage::parse("<source>:3:9: error: use of undeclared identifier 'x'").unwrap();
+    assert_eq!(message.file.as_deref(), Some("<source>"));
+    assert_eq!((message.line, message.column), (Some(3), Some(9)));
+    assert_eq!(message.severity, Severity::Error);
+    assert_eq!(message.text, "use of undeclared identifier 'x'");
+    assert!(BuildMessage::parse("    x = 1;").is_none());
+
+    // failed builds are described by their diagnostics
+    let program = RawProgram::with_source_in(&Global, "__kernel void f () {\n    x = 1;\n}")?;
+    let err = program.build_in(&Global, None).unwrap_err();
+    let diagnostics = program.build_diagnostics()?;
+    assert_eq!(diagnostics.devices().len(), program.devices()?.len());
+    assert!(diagnostics.has_errors());
+    if let Some(line) = diagnostics.messages().find_map(|x| x.line) {
+        assert_eq!(line, 2);
+        assert!(err.to_string().contains('^'));
+    }
+
+    // lines of a prefix are skipped
+    let lines = diagnostics.messages().map(|x| x.line).collect::<Vec<_>>();
+    let diagnostics = diagnostics.with_prefix_lines(1);
+    for (message, line) in diagnostics.messages().zip(lines) {
+        assert_eq!(message.line, line.map(|x| x - 1).filter(|x| *x > 0));
+    }
+
+    // successful builds keep their warnings
+    let (program, _) = RawProgram::from_source(KERNEL, None)?;
+    assert!(!program.build_diagnostics()?.has_errors());
+    Ok(())
+}
+
+#[test]
+fn options() -> Result<()> {
+    let options = BuildOptions::new()
+        .define("USE_TANH")
+        .define_value("SCALE", 2)
+        .std(Version::CL1_2)
+        .fast_math()
+        .warnings_as_errors();
+    assert_eq!(
+        options.as_str(),
+        "-D USE_TANH -D SCALE=2 -cl-std=CL1.2 -cl-fast-relaxed-math -Werror"
+    );
+
+    let preprocessor = Preprocessor::new()
+        .with_file("common.cl", "#pragma once\ntypedef float real;")
+        .with_file(
+            "math/scale.cl",
+            "#include \"../common.cl\"\n#define APPLY(x) ((x) * SCALE)",
+        );
+
+    let source = preprocessor.process(
+        "#include \"common.cl\"\n#include \"math/scale.cl\"\n__kernel void apply (__global real* x) {\n    x[get_global_id(0)] = APPLY(x[get_global_id(0)]);\n}",
+    )?;
+    assert_eq!(source.matches("typedef float real;").count(), 1);
+    assert!(source.contains("#line 1 \"math/scale.cl\""));
+    assert!(source.contains("#line 3 \"<source>\"\n__kernel void apply"));
+
+    assert!(preprocessor.process("#include \"missing.cl\"").is_err());
+    assert!(Preprocessor::new()
+        .with_file("a.cl", "#include \"b.cl\"")
+        .with_file("b.cl", "#include \"a.cl\"")
+        .process("#include \"a.cl\"")
+        .is_err());
+
+    let (_, kernels) = RawProgram::from_source(source, Some(options.as_str()))?;
+    assert_eq!(kernels.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn kernel_pool() -> Result<()> {
+    let (_, kernels) = RawProgram::from_source(KERNEL, None)?;
+    let pool = KernelPool::new(kernels.into_vec().swap_remove(0))?;
+
+    // concurrent instances are independent kernel objects
+    let first = pool.get()?;
+    let second = pool.get()?;
+    assert_ne!(first.id(), second.id());
+    assert_eq!(first.name()?, pool.kernel().name()?);
+    drop((first, second));
+    assert_eq!(pool.idle_count(), 2);
+
+    // generated programs can be launched from several threads at once
+    let tanh = FloatTanh::new(None)?;
+    std::thread::scope(|s| {
+        let handles = (0..4)
+            .map(|i| {
+                let tanh = &tanh;
+                s.spawn(move || -> Result<()> {
+                    let x = i as f32 / 4.0;
+                    let mut buf = buffer![x; 256]?;
+                    unsafe { tanh.forward_blocking(256, &mut buf, [256], None, None)? };
+                    assert!(buf.read_blocking(.., None)?.iter().all(|y| *y == x.tanh()));
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .try_for_each(|handle| handle.join().unwrap())
+    })
+}
+
+#[test]
+fn generic() -> Result<()> {
+    let source = specialize_source(&[TypeParam::of::<f64>("T")], AXPY);
+    assert!(source.starts_with("#pragma OPENCL EXTENSION cl_khr_fp64 : enable\ntypedef double T;"));
+    assert!(source.contains("#define T_PRECISION 64\n#define T_IS_FLOAT 1"));
+    assert!(source.ends_with(&format!("#line 1\n{AXPY}")));
+
+    let x = buffer![1i32, 2, 3, 4]?;
+    let mut y = buffer![1i32; 4]?;
+    let ints = Blas::<i32>::new(None)?;
+    unsafe { ints.axpy_blocking(4, 2, &x, &mut y, [4], None, None)? };
+    assert_eq!(y.read_blocking(.., None)?, vec![3, 5, 7, 9]);
+
+    let x = buffer![1f32, 2.0, 3.0, 4.0]?;
+    let mut y = buffer![0f32; 4]?;
+    let floats = Blas::<f32>::new(None)?;
+    unsafe { floats.axpy_blocking(4, 0.5, &x, &mut y, [4], None, None)? };
+    assert_eq!(y.read_blocking(.., None)?, vec![0.5, 1.0, 1.5, 2.0]);
+
+    // instances share the program built for their context
+    let again = Blas::<f32>::new(None)?;
+    assert_eq!(again.id(), floats.id());
+    assert_ne!(ints.id(), floats.id());
+    assert!(CONTEXT.specializations().unwrap().len() >= 2);
+
+    // specializations are freed alongside their context
+    let device = RawDevice::first().ok_or(ErrorKind::InvalidDevice)?;
+    let ctx = SimpleContext::new(
+        device,
+        ContextProperties::default(),
+        QueueProperties::default(),
+    )?;
+    let local = Blas::<f32, SimpleContext>::new_in(ctx, None)?;
+    assert_ne!(local.id(), floats.id());
+    assert_eq!(local.context().specializations().unwrap().len(), 1);
+    Ok(())
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn half() {
+    let source = specialize_source(&[TypeParam::of::<half::f16>("T")], AXPY);
+    assert!(source.starts_with("#pragma OPENCL EXTENSION cl_khr_fp16 : enable\ntypedef half T;"));
+    assert!(source.contains("#define T_PRECISION 16\n#define T_IS_FLOAT 1"));
+}
+
+#[test]
+fn images() -> Result<()> {
+    let volumes = Volumes::new(None)?;
+
+    // memory objects are checked to be images of the declared type
+    let buf = buffer![0f32; 64]?;
+    let mut mem = RawMemObject::clone(&buf);
+    let err = unsafe { volumes.fill_volume_blocking(&mut mem, 1.0, [4, 4, 4], None, None) };
+    assert_eq!(
+        err.unwrap_err().ty,
+        ErrorCode::Kind(ErrorKind::InvalidMemObject)
+    );
+
+    // samplers don't need the `image` feature
+    let sampler = Sampler::new(SamplerProperties::default())?;
+    let src = RawMemObject::clone(&buf);
+    let err =
+        unsafe { volumes.sample_layers_blocking(&src, &sampler, &mut mem, [4, 4, 4], None, None) };
+    assert_eq!(
+        err.unwrap_err().ty,
+        ErrorCode::Kind(ErrorKind::InvalidMemObject)
+    );
+    Ok(())
+}
+
+#[cfg(feature = "cl2")]
+#[test]
+fn pipes() -> Result<()> {
+    use blaze_rs::{
+        buffer::flags::MemAccess,
+        core::{Pipe, RawPipe},
+    };
+
+    let pipes = Pipes::new(None)?;
+    let mut pipe = Pipe::<u32>::new(64)?;
+    unsafe { pipes.produce_blocking(2, &mut pipe, [64], None, None)? };
+
+    let mut out = buffer![u32::MAX; 64]?;
+    unsafe { pipes.consume_blocking(&pipe, &mut out, [64], None, None)? };
+    let mut out = out.read_blocking(.., None)?;
+    out.sort_unstable();
+    assert_eq!(out, (0..64).map(|x| 2 * x).collect::<Vec<_>>());
+
+    // packets are checked against the limits of the devices
+    assert_eq!(
+        Pipe::<[u8; 1 << 20]>::new(1).unwrap_err().ty,
+        ErrorCode::Kind(ErrorKind::InvalidPipeSize)
+    );
+
+    // the packets of raw pipes must have the size of the declared type
+    let mut wide = RawPipe::new(MemAccess::READ_WRITE, false, 8, 64)?;
+    let err = unsafe { pipes.produce_blocking(2, &mut wide, [64], None, None) };
+    assert_eq!(
+        err.unwrap_err().ty,
+        ErrorCode::Kind(ErrorKind::InvalidArgValue)
+    );
+    Ok(())
+}
+
+#[test]
+fn extents() -> Result<()> {
+    let blas = SafeBlas::new(None)?;
+    let x = buffer![1f32, 2.0, 3.0, 4.0]?;
+    let mut y = buffer![1f32; 4]?;
+
+    blas.saxpy_blocking(4, 2.0, &x, &mut y, [4], None, None)?;
+    assert_eq!(y.read_blocking(.., None)?, vec![3.0, 5.0, 7.0, 9.0]);
+
+    // the kernel would read and write past the end of the buffers
+    let err = blas
+        .saxpy_blocking(8, 2.0, &x, &mut y, [8], None, None)
+        .unwrap_err();
+    assert_eq!(err.ty, ErrorCode::Kind(ErrorKind::InvalidArgValue));
+    assert_eq!(y.read_blocking(.., None)?, vec![3.0, 5.0, 7.0, 9.0]);
+    Ok(())
+}
+
+#[cfg(feature = "futures")]
+#[tokio::test]
+async fn futures() -> Result<()> {
+    let blas = SafeBlas::new(None)?;
+    let x = buffer![1f32, 2.0, 3.0, 4.0]?;
+    let mut y = buffer![1f32; 4]?;
+
+    blas.saxpy_async(4, 2.0, &x, &mut y, [4], None, None)
+        .await?;
+    assert_eq!(y.read_async(.., None).await?, vec![3.0, 5.0, 7.0, 9.0]);
+
+    let handle = tokio::spawn(async move {
+        let tanh = FloatTanh::new(None)?;
+        let mut buf = buffer![0f32; 4]?;
+        unsafe { tanh.forward_async(4, &mut buf, [4], None, None).await? };
+        buf.read_async(.., None).await
+    });
+
+    assert_eq!(handle.await.unwrap()?, vec![0.0; 4]);
+    Ok(())
+}
+
+#[cfg(feature = "cl2_1")]
+#[test]
+fn link_il() -> Result<()> {
+    let mut buf = buffer![0u32; 4]?;
+    let program = DefaultFill::new(None)?;
+    unsafe { program.fill_blocking(&mut buf, [4], None, None)? };
+    assert_eq!(buf.read_blocking(.., None)?, vec![7; 4]);
+
+    #[cfg(feature = "cl2_2")]
+    {
+        let program = SpecializedFill::new(None)?;
+        unsafe { program.fill_specialized_blocking(&mut buf, [4], None, None)? };
+        assert_eq!(buf.read_blocking(.., None)?, vec![42; 4]);
+    }
+
+    Ok(())
+}